@@ -0,0 +1,109 @@
+// Compare the speed of the lexer backends, and of serial and parallel tokenization of texts.
+// Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use pasta_tcs::parallel::{
+    available_threads, tokenize_text, tokenize_text_parallel, TokenizedText,
+};
+use pasta_tcs::tokenize::{tokenize_with, Backend, Lexer};
+
+/// The number of times each input is lexed
+const ITERATIONS: u32 = 200;
+
+/// The number of times each text is lexed
+const TEXT_ITERATIONS: u32 = 5;
+
+/// The number of lines of the text
+const LINES: usize = 200_000;
+
+/// A long valid expression mixing integers, decimals, operators and whitespace
+fn long_expression(terms: usize) -> String {
+    let mut string = String::new();
+    for i in 0..terms {
+        if i != 0 {
+            string.push_str(["+ ", "- ", "*", " / "][i % 4]);
+        }
+        if i % 2 == 0 {
+            string.push_str(&format!("{} ", 1 + i * 7919));
+        } else {
+            string.push_str(&format!("0.{}", i * 104729));
+        }
+    }
+    string
+}
+
+/// Many expressions, one per line, with an error every 100 lines
+fn many_lines(lines: usize) -> String {
+    (0..lines)
+        .map(|i| match i % 100 {
+            0 => "1.5 + 2\n".to_string(),
+            _ => format!("{} * 0.{} - {}\n", i, i % 997, i / 3),
+        })
+        .collect()
+}
+
+fn bench_text(name: &str, input: &str, tokenize: impl Fn(&str) -> TokenizedText) -> Duration {
+    let start = Instant::now();
+    for _ in 0..TEXT_ITERATIONS {
+        black_box(tokenize(black_box(input)));
+    }
+    let elapsed = start.elapsed();
+
+    let per_char = elapsed.as_nanos() as f64 / (TEXT_ITERATIONS as f64 * input.len() as f64);
+    println!("{:<30} {:>10.2?} {:>8.2} ns/char", name, elapsed, per_char);
+    elapsed
+}
+
+fn bench(name: &str, input: &str, backend: Backend) -> Duration {
+    let mut lexer = Lexer::with_backend(backend);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(tokenize_with(&mut lexer, black_box(input)).unwrap());
+    }
+    let elapsed = start.elapsed();
+
+    let per_char = elapsed.as_nanos() as f64 / (ITERATIONS as f64 * input.len() as f64);
+    println!("{:<30} {:>10.2?} {:>8.2} ns/char", name, elapsed, per_char);
+    elapsed
+}
+
+fn main() {
+    let input = long_expression(10_000);
+    println!(
+        "input: {} characters, {} iterations\n",
+        input.len(),
+        ITERATIONS
+    );
+
+    let hand_written = bench("hand-written", &input, Backend::HandWritten);
+    let table_driven = bench("table-driven", &input, Backend::TableDriven);
+
+    println!(
+        "\ntable-driven / hand-written: {:.2}",
+        table_driven.as_secs_f64() / hand_written.as_secs_f64()
+    );
+
+    let text = many_lines(LINES);
+    let threads = available_threads();
+    println!(
+        "\ntext: {} lines, {} characters, {} iterations, {} threads\n",
+        LINES,
+        text.len(),
+        TEXT_ITERATIONS,
+        threads
+    );
+
+    assert_eq!(tokenize_text_parallel(&text, threads), tokenize_text(&text));
+    let serial = bench_text("serial", &text, tokenize_text);
+    let parallel = bench_text(&format!("parallel ({} threads)", threads), &text, |text| {
+        tokenize_text_parallel(text, threads)
+    });
+
+    println!(
+        "\nserial / parallel: {:.2}",
+        serial.as_secs_f64() / parallel.as_secs_f64()
+    );
+}
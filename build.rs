@@ -0,0 +1,27 @@
+// Generate the default lexer from its specification in src/grammar.lex
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+#[allow(dead_code)]
+#[path = "src/spec.rs"]
+mod spec;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/grammar.lex");
+    println!("cargo:rerun-if-changed=src/spec.rs");
+
+    let source = fs::read_to_string("src/grammar.lex").unwrap();
+    let compiled = match spec::compile(&source) {
+        Ok(compiled) => compiled,
+        Err(error) => panic!("src/grammar.lex:{}: {:?}", error.line, error.kind),
+    };
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let generated = format!(
+        "// Generated by build.rs from src/grammar.lex\n\n{}",
+        compiled.to_rust()
+    );
+    fs::write(Path::new(&out_dir).join("grammar.rs"), generated).unwrap();
+}
@@ -0,0 +1,18 @@
+use std::io;
+use std::process::ExitCode;
+
+use pasta_tcs::lsp::Server;
+
+// Serve the Language Server Protocol over stdin and stdout
+fn main() -> ExitCode {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    match Server::new().run(&mut stdin.lock(), &mut stdout.lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("pasta-lsp: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
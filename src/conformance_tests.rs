@@ -0,0 +1,131 @@
+use regex::Regex;
+
+use crate::differential::exhaustive_inputs;
+use crate::input_class::InputClass;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{
+    get_operator_kind, ExpressionLexingError, Lexer, LexingError, NumberLexingError, State, Token,
+};
+use crate::transitions::transition_table;
+
+/// The longest enumerated input
+const MAX_LEN: usize = 5;
+
+/// The expressions accepted by the lexer, as a single regular expression
+const ACCEPTED: &str = r"^ *NUMBER(?: *[+\-*/] *NUMBER)* *$";
+const NUMBER: &str = r"(?:0\.[0-9]+|0|[1-9][0-9]*)";
+
+/// What the enumeration went through
+#[derive(Default)]
+struct Coverage {
+    transitions: Vec<(State, InputClass)>,
+    number_errors: Vec<NumberLexingError>,
+    expression_errors: Vec<ExpressionLexingError>,
+}
+
+/// A number or an operator of an accepted expression
+const LEXEME: &str = r"[0-9.]+|[+\-*/]";
+
+/// The tokens of an accepted expression, read off the oracle's lexemes
+fn oracle_tokens(lexeme: &Regex, input: &str) -> Vec<Token> {
+    lexeme
+        .find_iter(input)
+        .map(|lexeme| {
+            let string = lexeme.as_str();
+            match string.chars().next().and_then(get_operator_kind) {
+                Some(kind) => Token::Operator(kind),
+                None => Token::Number(string.parse().unwrap()),
+            }
+        })
+        .collect()
+}
+
+/// Feed the string one character at a time, recording every transition taken
+fn tokenize_recording(input: &str, coverage: &mut Coverage) -> Result<Vec<Token>, LexingError> {
+    let mut lexer = Lexer::new();
+    let mut tokens = vec![];
+
+    for c in input.chars().map(Some).chain(std::iter::once(None)) {
+        let transition = (lexer.state(), InputClass::of(c));
+        if !coverage.transitions.contains(&transition) {
+            coverage.transitions.push(transition);
+        }
+        tokens.append(&mut lexer.feed(c)?);
+    }
+
+    assert!(lexer.is_ended());
+    Ok(tokens)
+}
+
+#[test]
+fn test_exhaustive_conformance() {
+    let accepted = Regex::new(&ACCEPTED.replace("NUMBER", NUMBER)).unwrap();
+    let lexeme = Regex::new(LEXEME).unwrap();
+    let mut coverage = Coverage::default();
+
+    for input in exhaustive_inputs(&ALPHABET, MAX_LEN) {
+        match tokenize_recording(&input, &mut coverage) {
+            Ok(tokens) => {
+                assert!(accepted.is_match(&input), "\n accepted: {:?}\n\n", input);
+                assert_eq!(
+                    tokens,
+                    oracle_tokens(&lexeme, &input),
+                    "\n input: {:?}\n\n",
+                    input
+                );
+            }
+            Err(error) => {
+                assert!(!accepted.is_match(&input), "\n rejected: {:?}\n\n", input);
+                match error {
+                    LexingError::IncorrectNumber(error) => {
+                        if !coverage.number_errors.contains(&error) {
+                            coverage.number_errors.push(error);
+                        }
+                    }
+                    LexingError::IncorrectExpression(error) => {
+                        if !coverage.expression_errors.contains(&error) {
+                            coverage.expression_errors.push(error);
+                        }
+                    }
+                    LexingError::IncorrectEncoding(error) => {
+                        panic!("\n input: {:?}\n error: {:?}\n\n", input, error)
+                    }
+                }
+            }
+        }
+    }
+
+    // Every transition out of a live state is taken
+    for transition in transition_table() {
+        if transition.from == State::End || transition.from == State::Error {
+            continue;
+        }
+        assert!(
+            coverage
+                .transitions
+                .contains(&(transition.from, transition.input)),
+            "\n not reached: {:?}\n\n",
+            transition
+        );
+    }
+
+    // Every error variant is raised
+    let number_errors = [
+        NumberLexingError::ExpectedDigitAfterPoint,
+        NumberLexingError::NonZeroIntegerBeforePoint,
+        NumberLexingError::MissingIntegerBeforePoint,
+        NumberLexingError::ExpectedPointAfterZero,
+    ];
+    for error in number_errors.iter() {
+        assert!(coverage.number_errors.contains(error), "{:?}", error);
+    }
+
+    let expression_errors = [
+        ExpressionLexingError::UnexpectedCharacter('x'),
+        ExpressionLexingError::ExpectedNumber,
+        ExpressionLexingError::ExpectedOperator,
+    ];
+    for error in expression_errors.iter() {
+        assert!(coverage.expression_errors.contains(error), "{:?}", error);
+    }
+}
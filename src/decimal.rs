@@ -0,0 +1,215 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use num_bigint::{BigInt, Sign};
+
+// An exact decimal number, `mantissa * 10^-scale`, of any length.
+// The scale is the number of decimals it was written with, so equality is that of lexemes:
+// "0.5" and "0.50" are different decimals with the same value, see `normalize`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: BigInt,
+    scale: u32,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ParseDecimalError {
+    Empty,
+    InvalidCharacter(char),
+}
+
+// How a quotient with more decimals than wanted is rounded
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Rounding {
+    // Towards zero
+    Down,
+    // Away from zero
+    Up,
+    // Towards negative infinity
+    Floor,
+    // Towards positive infinity
+    Ceiling,
+    // To the nearest, ties towards zero
+    HalfDown,
+    // To the nearest, ties away from zero
+    HalfUp,
+    // To the nearest, ties to an even last digit
+    HalfEven,
+}
+
+fn power_of_ten(exponent: u32) -> BigInt {
+    BigInt::from(10u32).pow(exponent)
+}
+
+impl Decimal {
+    pub fn new(mantissa: BigInt, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.sign() == Sign::NoSign
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.sign() == Sign::Minus
+    }
+
+    // The same value with more decimals
+    fn with_scale(&self, scale: u32) -> BigInt {
+        debug_assert!(scale >= self.scale);
+        &self.mantissa * power_of_ten(scale - self.scale)
+    }
+
+    // The same value with the fewest decimals, e.g. "1.50" and "1.5" both give "1.5"
+    pub fn normalize(&self) -> Self {
+        let ten = BigInt::from(10u32);
+        let mut normalized = self.clone();
+
+        while normalized.scale > 0 && (&normalized.mantissa % &ten).sign() == Sign::NoSign {
+            normalized.mantissa /= &ten;
+            normalized.scale -= 1;
+        }
+
+        normalized
+    }
+
+    // The quotient rounded to `scale` decimals, `None` when dividing by zero
+    pub fn checked_div(&self, divisor: &Self, scale: u32, rounding: Rounding) -> Option<Self> {
+        if divisor.is_zero() {
+            return None;
+        }
+
+        // self / divisor * 10^scale, as a fraction of integers
+        let numerator = &self.mantissa * power_of_ten(scale + divisor.scale);
+        let denominator = &divisor.mantissa * power_of_ten(self.scale);
+        let mut quotient = &numerator / &denominator;
+        let remainder = &numerator % &denominator;
+
+        if remainder.sign() != Sign::NoSign {
+            let negative = numerator.sign() != denominator.sign();
+            let half = (remainder.magnitude() * 2u32).cmp(denominator.magnitude());
+
+            let away_from_zero = match rounding {
+                Rounding::Down => false,
+                Rounding::Up => true,
+                Rounding::Floor => negative,
+                Rounding::Ceiling => !negative,
+                Rounding::HalfDown => half.is_gt(),
+                Rounding::HalfUp => half.is_ge(),
+                Rounding::HalfEven => half.is_gt() || (half.is_eq() && quotient.magnitude().bit(0)),
+            };
+            if away_from_zero {
+                quotient += if negative { -1 } else { 1 };
+            }
+        }
+
+        Some(Self::new(quotient, scale))
+    }
+
+    // The nearest float, as `Token::Number` holds
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+}
+
+// ====================
+// Parsing and writing
+// ====================
+
+// Digits with an optional point and sign, like "-12.50"
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match string.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, string),
+        };
+        let (integer, decimals) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if integer.is_empty() && decimals.is_empty() {
+            return Err(ParseDecimalError::Empty);
+        }
+        if let Some(c) = integer
+            .chars()
+            .chain(decimals.chars())
+            .find(|c| !c.is_ascii_digit())
+        {
+            return Err(ParseDecimalError::InvalidCharacter(c));
+        }
+
+        let digits = format!("{}{}", integer, decimals);
+        let mantissa = BigInt::parse_bytes(digits.as_bytes(), 10).unwrap();
+
+        Ok(Self::new(
+            if negative { -mantissa } else { mantissa },
+            decimals.len() as u32,
+        ))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.magnitude().to_string();
+        let scale = self.scale as usize;
+        // At least one digit before the point
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer, decimals) = digits.split_at(digits.len() - scale);
+
+        if self.is_negative() {
+            write!(f, "-")?;
+        }
+        match decimals {
+            "" => write!(f, "{}", integer),
+            decimals => write!(f, "{}.{}", integer, decimals),
+        }
+    }
+}
+
+// ====================
+// Arithmetic
+// ====================
+
+// Sums and differences have the decimals of the operand with the most, products the decimals
+// of both operands
+impl Add for &Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(self.with_scale(scale) + other.with_scale(scale), scale)
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(self.with_scale(scale) - other.with_scale(scale), scale)
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: &Decimal) -> Decimal {
+        Decimal::new(&self.mantissa * &other.mantissa, self.scale + other.scale)
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal::new(-self.mantissa, self.scale)
+    }
+}
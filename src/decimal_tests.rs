@@ -0,0 +1,289 @@
+use num_bigint::BigInt;
+use rand::Rng;
+
+use crate::decimal::{Decimal, ParseDecimalError, Rounding};
+use crate::differential::random_input;
+use crate::evaluate::{evaluate_decimal, DecimalContext, EvaluationError};
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{
+    tokenize, tokenize_with, Backend, Lexer, LexingError, NumberRepresentation, OperatorKind, Token,
+};
+
+fn decimal(string: &str) -> Decimal {
+    string.parse().unwrap()
+}
+
+fn tokenize_decimal(string: &str) -> Result<Vec<Token>, LexingError> {
+    tokenize_with(
+        &mut Lexer::with_numbers(NumberRepresentation::Decimal),
+        string,
+    )
+}
+
+fn evaluate_string(string: &str, context: &DecimalContext) -> Result<String, EvaluationError> {
+    evaluate_decimal(&tokenize_decimal(string).unwrap(), context).map(|value| value.to_string())
+}
+
+/// A random decimal with up to 30 digits, and up to 10 of them after the point
+fn random_decimal(rng: &mut impl Rng) -> Decimal {
+    let digits: String = (0..rng.gen_range(1..=30))
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect();
+    let mantissa = BigInt::parse_bytes(digits.as_bytes(), 10).unwrap();
+    let mantissa = if rng.gen_bool(0.5) {
+        -mantissa
+    } else {
+        mantissa
+    };
+
+    Decimal::new(mantissa, rng.gen_range(0..=10))
+}
+
+#[test]
+fn test_parse_and_display() {
+    for string in [
+        "0",
+        "0.1",
+        "0.50",
+        "-12.034",
+        "123456789012345678901234567890.5",
+    ] {
+        assert_eq!(decimal(string).to_string(), string);
+    }
+
+    assert_eq!(decimal("0.50").scale(), 2);
+    assert_eq!(decimal("0.50").mantissa(), &BigInt::from(50));
+    assert_eq!(decimal(".5").to_string(), "0.5");
+    assert_eq!(decimal("7.").to_string(), "7");
+    assert_eq!("".parse::<Decimal>(), Err(ParseDecimalError::Empty));
+    assert_eq!("-.".parse::<Decimal>(), Err(ParseDecimalError::Empty));
+    assert_eq!(
+        "1.2.3".parse::<Decimal>(),
+        Err(ParseDecimalError::InvalidCharacter('.'))
+    );
+    assert_eq!(
+        "1e5".parse::<Decimal>(),
+        Err(ParseDecimalError::InvalidCharacter('e'))
+    );
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(decimal("1.500").normalize(), decimal("1.5"));
+    assert_eq!(decimal("10.0").normalize(), decimal("10"));
+    assert_eq!(decimal("0.000").normalize(), decimal("0"));
+    assert_ne!(decimal("0.5"), decimal("0.50"));
+}
+
+#[test]
+fn test_arithmetic() {
+    assert_eq!(&decimal("0.1") + &decimal("0.2"), decimal("0.3"));
+    assert_eq!(&decimal("1.50") + &decimal("1"), decimal("2.50"));
+    assert_eq!(&decimal("0.1") - &decimal("0.25"), decimal("-0.15"));
+    assert_eq!(&decimal("19.99") * &decimal("3"), decimal("59.97"));
+    assert_eq!(&decimal("-0.5") * &decimal("0.5"), decimal("-0.25"));
+    assert_eq!(-decimal("2.5"), decimal("-2.5"));
+}
+
+#[test]
+fn test_division_rounding() {
+    use Rounding::*;
+
+    // The quotients rounded to an integer, for every rounding
+    let cases = [
+        (
+            "5",
+            "2",
+            [("2", Down), ("3", Up), ("2", Floor), ("3", Ceiling)],
+        ),
+        (
+            "-5",
+            "2",
+            [("-2", Down), ("-3", Up), ("-3", Floor), ("-2", Ceiling)],
+        ),
+        (
+            "7",
+            "-3",
+            [("-2", Down), ("-3", Up), ("-3", Floor), ("-2", Ceiling)],
+        ),
+    ];
+    for (dividend, divisor, expected) in cases.iter() {
+        for (quotient, rounding) in expected.iter() {
+            assert_eq!(
+                decimal(dividend).checked_div(&decimal(divisor), 0, *rounding),
+                Some(decimal(quotient)),
+                "\n {} / {} rounded {:?}\n\n",
+                dividend,
+                divisor,
+                rounding
+            );
+        }
+    }
+
+    let half = |dividend: &str, rounding| {
+        decimal(dividend)
+            .checked_div(&decimal("2"), 0, rounding)
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(
+        [half("5", HalfDown), half("5", HalfUp), half("5", HalfEven)],
+        ["2", "3", "2"]
+    );
+    assert_eq!(
+        [half("7", HalfDown), half("7", HalfUp), half("7", HalfEven)],
+        ["3", "4", "4"]
+    );
+    assert_eq!(
+        [
+            half("-5", HalfDown),
+            half("-5", HalfUp),
+            half("-5", HalfEven)
+        ],
+        ["-2", "-3", "-2"]
+    );
+    assert_eq!(half("6", HalfDown), "3");
+    assert_eq!(half("5.2", HalfDown), "3");
+
+    // Quotients have exactly the scale asked for
+    assert_eq!(
+        decimal("2").checked_div(&decimal("3"), 5, HalfEven),
+        Some(decimal("0.66667"))
+    );
+    assert_eq!(
+        decimal("0.01").checked_div(&decimal("0.4"), 4, HalfEven),
+        Some(decimal("0.0250"))
+    );
+    assert_eq!(
+        decimal("-0.001").checked_div(&decimal("3"), 2, Floor),
+        Some(decimal("-0.01"))
+    );
+    assert_eq!(decimal("1").checked_div(&decimal("0.00"), 2, Up), None);
+}
+
+#[test]
+fn test_decimal_tokens() {
+    assert_eq!(
+        tokenize_decimal("0.1 + 123456789012345678901234567890*0.50"),
+        Ok(vec![
+            Token::Decimal(decimal("0.1")),
+            Token::Operator(OperatorKind::Add),
+            Token::Decimal(decimal("123456789012345678901234567890")),
+            Token::Operator(OperatorKind::Multiply),
+            Token::Decimal(decimal("0.50")),
+        ])
+    );
+
+    let mut lexer = Lexer::line_oriented().numbers(NumberRepresentation::Decimal);
+    assert_eq!(
+        tokenize_with(&mut lexer, "0.1\n2"),
+        Ok(vec![
+            Token::Decimal(decimal("0.1")),
+            Token::EndOfExpression,
+            Token::Decimal(decimal("2")),
+            Token::EndOfExpression,
+        ])
+    );
+}
+
+#[test]
+fn test_decimal_tokens_match_float_tokens() {
+    let mut table_driven =
+        Lexer::with_backend(Backend::TableDriven).numbers(NumberRepresentation::Decimal);
+
+    seeded::check(
+        5000,
+        || random_input(&ALPHABET, 30),
+        |input| {
+            let decimals = tokenize_decimal(input);
+            assert_eq!(tokenize_with(&mut table_driven.clone(), input), decimals);
+
+            let floats = decimals.map(|tokens| {
+                tokens
+                    .into_iter()
+                    .map(|token| match token {
+                        Token::Decimal(decimal) => Token::Number(decimal.to_f64()),
+                        token => token,
+                    })
+                    .collect()
+            });
+            assert_eq!(floats, tokenize(input));
+        },
+    );
+    table_driven.reset();
+}
+
+#[test]
+fn test_evaluate_decimal() {
+    let context = DecimalContext::default();
+
+    assert_eq!(
+        evaluate_string("0.1 + 0.2", &context),
+        Ok("0.3".to_string())
+    );
+    assert_eq!(
+        evaluate_string("0.99 * 3 - 0.01", &context),
+        Ok("2.96".to_string())
+    );
+    assert_eq!(
+        evaluate_string("1 - 8 / 4 * 3 + 2", &context),
+        Ok("-3.00000000000000000000".to_string())
+    );
+    assert_eq!(
+        evaluate_string("1 / 0.00", &context),
+        Err(EvaluationError::DivisionByZero)
+    );
+
+    let cents = DecimalContext {
+        scale: 2,
+        rounding: Rounding::HalfUp,
+    };
+    assert_eq!(evaluate_string("10 / 3", &cents), Ok("3.33".to_string()));
+    assert_eq!(evaluate_string("0.125 / 1", &cents), Ok("0.13".to_string()));
+    assert_eq!(
+        evaluate_string("100 / 3 * 3", &cents),
+        Ok("99.99".to_string())
+    );
+
+    // Floats are taken as the shortest decimal giving them back
+    assert_eq!(
+        evaluate_decimal(&tokenize("0.1 + 0.2").unwrap(), &context),
+        Ok(decimal("0.3"))
+    );
+}
+
+#[test]
+fn test_arithmetic_random() {
+    seeded::check(
+        2000,
+        || {
+            let mut rng = seeded::thread_rng();
+            (
+                random_decimal(&mut rng).to_string(),
+                random_decimal(&mut rng).to_string(),
+            )
+        },
+        |(a, b)| {
+            // Shrinking can leave something else than numbers
+            let (a, b) = match (a.parse::<Decimal>(), b.parse::<Decimal>()) {
+                (Ok(a), Ok(b)) => (a, b),
+                _ => return,
+            };
+
+            assert_eq!((&(&a + &b) - &b).normalize(), a.normalize());
+            assert_eq!(a.to_string().parse::<Decimal>(), Ok(a.clone()));
+
+            // Products divide back exactly
+            if !b.is_zero() {
+                assert_eq!(
+                    (&a * &b).checked_div(&b, a.scale(), Rounding::Down),
+                    Some(a.clone()),
+                    "\n a: {}\n b: {}\n\n",
+                    a,
+                    b
+                );
+            }
+        },
+    );
+}
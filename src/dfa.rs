@@ -0,0 +1,109 @@
+use std::sync::OnceLock;
+
+use crate::input_class::InputClass;
+use crate::tokenize::{get_operator_kind, Lexer, LexingError, State, Token};
+use crate::transitions::{transition, Emit};
+
+const STATE_COUNT: usize = State::ALL.len();
+const CLASS_COUNT: usize = InputClass::ALL.len();
+
+// ====================
+// Tables
+// ====================
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Action {
+    // Whether the character is pushed to the number buffer
+    pub push: bool,
+    pub emit: Emit,
+}
+
+pub struct DfaTables {
+    // The class of every ASCII character, all other characters are `InputClass::Other`
+    pub classes: [InputClass; 128],
+    // The next state, indexed by state and input class
+    pub next: [[State; CLASS_COUNT]; STATE_COUNT],
+    // The action to perform, indexed by state and input class
+    pub actions: [[Action; CLASS_COUNT]; STATE_COUNT],
+}
+
+impl DfaTables {
+    fn build() -> Self {
+        let mut classes = [InputClass::Other; 128];
+        for (byte, class) in classes.iter_mut().enumerate() {
+            *class = InputClass::of(Some(byte as u8 as char));
+        }
+
+        let mut next = [[State::Error; CLASS_COUNT]; STATE_COUNT];
+        let no_action = Action {
+            push: false,
+            emit: Emit::Nothing,
+        };
+        let mut actions = [[no_action; CLASS_COUNT]; STATE_COUNT];
+
+        for &state in State::ALL.iter() {
+            for &class in InputClass::ALL.iter() {
+                let t = transition(state, class);
+                next[state as usize][class as usize] = t.to;
+                actions[state as usize][class as usize] = Action {
+                    push: t.push,
+                    emit: t.emit,
+                };
+            }
+        }
+
+        Self {
+            classes,
+            next,
+            actions,
+        }
+    }
+
+    pub fn class_of(&self, c: Option<char>) -> InputClass {
+        match c {
+            None => InputClass::EndOfInput,
+            Some(c) if c.is_ascii() => self.classes[c as usize],
+            Some(_) => InputClass::Other,
+        }
+    }
+}
+
+// Get the tables, built from `crate::transitions` the first time they are needed
+pub fn tables() -> &'static DfaTables {
+    static TABLES: OnceLock<DfaTables> = OnceLock::new();
+    TABLES.get_or_init(DfaTables::build)
+}
+
+// ====================
+// Table-driven feed
+// ====================
+
+impl Lexer {
+    // Feed a character to the table-driven state machine
+    pub(crate) fn feed_table_driven(&mut self, c: Option<char>) -> Result<Vec<Token>, LexingError> {
+        let tables = tables();
+        let state = self.state as usize;
+        let class = tables.class_of(c) as usize;
+
+        let action = tables.actions[state][class];
+        self.state = tables.next[state][class];
+
+        if action.push {
+            // Only characters are ever pushed
+            self.buffer.extend(c);
+        }
+
+        let operator = || match c.and_then(get_operator_kind) {
+            Some(operator_kind) => Token::Operator(operator_kind),
+            None => unreachable!("operators are only emitted for operator characters"),
+        };
+
+        match action.emit {
+            Emit::Nothing => Ok(vec![]),
+            Emit::Number => Ok(vec![self.drain_buffer_to_number()]),
+            Emit::Operator => Ok(vec![operator()]),
+            Emit::NumberAndOperator => Ok(vec![self.drain_buffer_to_number(), operator()]),
+            Emit::Error(error) => Err(error.to_lexing_error(c)),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::dfa::tables;
+use crate::differential::random_input;
+use crate::input_class::InputClass;
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{tokenize_with, Backend, Lexer};
+
+/// The number of random inputs to compare
+const RANDOM_COUNT: u32 = 10000;
+
+/// Feed the string to both backends, comparing every result and state along the way
+fn assert_backends_agree(input: &str) {
+    let mut hand_written = Lexer::with_backend(Backend::HandWritten);
+    let mut table_driven = Lexer::with_backend(Backend::TableDriven);
+
+    for c in input.chars().map(Some).chain(std::iter::once(None)) {
+        let expected = hand_written.feed(c);
+        let actual = table_driven.feed(c);
+
+        assert_eq!(expected, actual, "\n input: \"{}\" at {:?}\n\n", input, c);
+        assert_eq!(
+            hand_written.state(),
+            table_driven.state(),
+            "\n input: \"{}\" at {:?}\n\n",
+            input,
+            c
+        );
+    }
+}
+
+/// Every string over the alphabet with exactly `len` characters
+fn all_strings(len: usize) -> Vec<String> {
+    (0..len).fold(vec![String::new()], |strings, _| {
+        strings
+            .iter()
+            .flat_map(|s| ALPHABET.iter().map(move |&c| format!("{}{}", s, c)))
+            .collect()
+    })
+}
+
+#[test]
+fn test_backends_agree_on_short_inputs() {
+    for len in 0..=5 {
+        for input in all_strings(len) {
+            assert_backends_agree(&input);
+        }
+    }
+}
+
+#[test]
+fn test_backends_agree_on_random_inputs() {
+    seeded::check(
+        RANDOM_COUNT,
+        || random_input(&ALPHABET, 29),
+        |input| assert_backends_agree(input),
+    );
+}
+
+#[test]
+fn test_backends_agree_on_valid_expressions() {
+    let inputs = vec![
+        "0.125 + 4 * 16 / 0.5 - 1000",
+        "  7  -0.0",
+        "0",
+        "0.1234567890",
+    ];
+
+    for input in inputs.into_iter() {
+        assert_backends_agree(input);
+
+        let mut lexer = Lexer::with_backend(Backend::TableDriven);
+        assert!(tokenize_with(&mut lexer, input).is_ok());
+    }
+}
+
+#[test]
+fn test_class_map() {
+    let tables = tables();
+
+    for byte in 0..128u8 {
+        let c = Some(byte as char);
+        assert_eq!(tables.class_of(c), InputClass::of(c));
+    }
+    assert_eq!(tables.class_of(Some('é')), InputClass::Other);
+    assert_eq!(tables.class_of(None), InputClass::EndOfInput);
+}
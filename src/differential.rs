@@ -0,0 +1,91 @@
+use rand::Rng;
+
+use crate::seeded;
+use crate::shrink::shrink;
+use crate::token_source::TokenSource;
+use crate::tokenize::{LexingError, Token};
+
+/// An input for which two token sources return different results
+#[derive(Debug, PartialEq)]
+pub struct Disagreement {
+    pub input: String,
+    pub expected: Result<Vec<Token>, LexingError>,
+    pub actual: Result<Vec<Token>, LexingError>,
+}
+
+/// Feed every input to both sources, returning the first one they disagree on
+pub fn first_disagreement(
+    expected: &mut impl TokenSource,
+    actual: &mut impl TokenSource,
+    inputs: impl IntoIterator<Item = String>,
+) -> Option<Disagreement> {
+    inputs.into_iter().find_map(|input| {
+        let expected = expected.tokenize(&input);
+        let actual = actual.tokenize(&input);
+
+        if expected == actual {
+            None
+        } else {
+            Some(Disagreement {
+                input,
+                expected,
+                actual,
+            })
+        }
+    })
+}
+
+/// The simplest input the sources still disagree on, found by shrinking the disagreement's input
+pub fn shrink_disagreement(
+    expected: &mut impl TokenSource,
+    actual: &mut impl TokenSource,
+    disagreement: Disagreement,
+) -> Disagreement {
+    let input = shrink(&disagreement.input, |input| {
+        expected.tokenize(input) != actual.tokenize(input)
+    });
+
+    Disagreement {
+        expected: expected.tokenize(&input),
+        actual: actual.tokenize(&input),
+        input,
+    }
+}
+
+/// Every string over the alphabet with at most `max_len` characters, shortest first
+pub fn exhaustive_inputs(alphabet: &[char], max_len: usize) -> impl Iterator<Item = String> + '_ {
+    (0..=max_len).flat_map(move |len| {
+        let count = alphabet.len().pow(len as u32);
+
+        // Count in base `alphabet.len()`, one digit per character
+        (0..count).map(move |mut index| {
+            let mut string = String::with_capacity(len);
+            for _ in 0..len {
+                string.push(alphabet[index % alphabet.len()]);
+                index /= alphabet.len();
+            }
+            string
+        })
+    })
+}
+
+/// `count` random strings over the alphabet with at most `max_len` characters
+pub fn random_inputs(
+    alphabet: &[char],
+    max_len: usize,
+    count: usize,
+) -> impl Iterator<Item = String> + '_ {
+    let mut rng = seeded::thread_rng();
+
+    (0..count).map(move |_| {
+        let len = rng.gen_range(0..=max_len);
+        (0..len)
+            .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+            .collect()
+    })
+}
+
+/// A random string over the alphabet with at most `max_len` characters
+pub fn random_input(alphabet: &[char], max_len: usize) -> String {
+    random_inputs(alphabet, max_len, 1).next().unwrap()
+}
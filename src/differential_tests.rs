@@ -0,0 +1,188 @@
+use crate::differential::{
+    exhaustive_inputs, first_disagreement, random_input, shrink_disagreement, Disagreement,
+};
+use crate::reference_lexer::ReferenceLexer;
+use crate::seeded;
+use crate::spec_lexer::generated_spec;
+use crate::test_helpers::ALPHABET;
+use crate::token_source::TokenSource;
+use crate::tokenize::{
+    tokenize, Backend, ExpressionLexingError, Lexer, LexingError, NumberLexingError, OperatorKind,
+    Token,
+};
+
+/// The longest exhaustively tested input
+const EXHAUSTIVE_LEN: usize = 5;
+
+/// The number and length of randomly tested inputs
+const RANDOM_COUNT: u32 = 20000;
+const RANDOM_LEN: usize = 40;
+
+/// Report a disagreement along with the minimal input the source still disagrees on
+fn assert_no_disagreement(
+    reference: &mut ReferenceLexer,
+    source: &mut impl TokenSource,
+    disagreement: Option<Disagreement>,
+) {
+    if let Some(disagreement) = disagreement {
+        let input = disagreement.input.clone();
+        let minimal = shrink_disagreement(reference, source, disagreement);
+
+        panic!(
+            "\n input: {:?}\n minimal input: {:?}\n reference: {:?}\n lexer: {:?}\n\n",
+            input, minimal.input, minimal.expected, minimal.actual
+        );
+    }
+}
+
+/// Compare the source with the reference on random inputs, each generated from its own seed.
+/// `seeded::check` shrinks the failing input, so the disagreement isn't shrunk here.
+fn assert_agree_on_random_inputs(source: &mut impl TokenSource) {
+    let mut reference = ReferenceLexer::new();
+
+    seeded::check(
+        RANDOM_COUNT,
+        || random_input(&ALPHABET, RANDOM_LEN),
+        |input| {
+            let disagreement = first_disagreement(&mut reference, source, Some(input.to_string()));
+            assert_eq!(disagreement, None);
+        },
+    );
+}
+
+#[test]
+fn test_reference_examples() {
+    let reference = ReferenceLexer::new();
+    let cases = vec![
+        (
+            " 0.5*12 ",
+            Ok(vec![
+                Token::Number(0.5),
+                Token::Operator(OperatorKind::Multiply),
+                Token::Number(12.0),
+            ]),
+        ),
+        (
+            "0.5.",
+            Err(LexingError::IncorrectNumber(
+                NumberLexingError::NonZeroIntegerBeforePoint,
+            )),
+        ),
+        (
+            "1 + 0.",
+            Err(LexingError::IncorrectNumber(
+                NumberLexingError::ExpectedDigitAfterPoint,
+            )),
+        ),
+        (
+            "3 -\n",
+            Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::UnexpectedCharacter('\n'),
+            )),
+        ),
+        (
+            "0 2",
+            Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::ExpectedOperator,
+            )),
+        ),
+    ];
+
+    for (input, expected) in cases.into_iter() {
+        assert_eq!(
+            reference.tokenize(input),
+            expected,
+            "\n input: {:?}\n\n",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_hand_written_exhaustive() {
+    let mut reference = ReferenceLexer::new();
+    let mut lexer = Lexer::with_backend(Backend::HandWritten);
+
+    let disagreement = first_disagreement(
+        &mut reference,
+        &mut lexer,
+        exhaustive_inputs(&ALPHABET, EXHAUSTIVE_LEN),
+    );
+    assert_no_disagreement(&mut reference, &mut lexer, disagreement);
+}
+
+#[test]
+fn test_hand_written_random() {
+    assert_agree_on_random_inputs(&mut Lexer::with_backend(Backend::HandWritten));
+}
+
+#[test]
+fn test_table_driven_random() {
+    assert_agree_on_random_inputs(&mut Lexer::with_backend(Backend::TableDriven));
+}
+
+#[test]
+fn test_generated_spec_random() {
+    assert_agree_on_random_inputs(&mut generated_spec().clone());
+}
+
+#[test]
+fn test_first_disagreement_is_reported() {
+    // A broken lexer which accepts integers starting with zero
+    let mut broken = |string: &str| tokenize(string.trim_start_matches('0'));
+
+    let disagreement = first_disagreement(
+        &mut ReferenceLexer::new(),
+        &mut broken,
+        exhaustive_inputs(&['0', '1'], 3),
+    );
+
+    assert_eq!(
+        disagreement,
+        Some(Disagreement {
+            input: "0".to_string(),
+            expected: Ok(vec![Token::Number(0.0)]),
+            actual: Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::ExpectedNumber
+            )),
+        })
+    );
+}
+
+#[test]
+fn test_exhaustive_inputs() {
+    let inputs: Vec<String> = exhaustive_inputs(&['a', 'b'], 2).collect();
+
+    assert_eq!(inputs, vec!["", "a", "b", "aa", "ba", "ab", "bb"]);
+}
+
+#[test]
+fn test_disagreement_is_shrunk() {
+    // A broken lexer which drops expressions with two operators
+    let mut broken = |string: &str| match tokenize(string) {
+        Ok(tokens) if tokens.len() > 3 => Ok(vec![]),
+        result => result,
+    };
+    let disagreement = Disagreement {
+        input: "  123 * 0.75 -   9 / 40".to_string(),
+        expected: Ok(vec![]),
+        actual: Ok(vec![]),
+    };
+
+    let minimal = shrink_disagreement(&mut ReferenceLexer::new(), &mut broken, disagreement);
+
+    assert_eq!(
+        minimal,
+        Disagreement {
+            input: "0-0/0".to_string(),
+            expected: Ok(vec![
+                Token::Number(0.0),
+                Token::Operator(OperatorKind::Subtract),
+                Token::Number(0.0),
+                Token::Operator(OperatorKind::Divide),
+                Token::Number(0.0),
+            ]),
+            actual: Ok(vec![]),
+        }
+    );
+}
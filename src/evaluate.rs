@@ -0,0 +1,362 @@
+use num_bigint::{BigInt, Sign};
+
+use crate::decimal::{Decimal, Rounding};
+use crate::token_source::TokenSource;
+use crate::tokenize::{LexingError, OperatorKind, Token};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum EvaluationError {
+    ExpectedNumber,
+    ExpectedOperator,
+    DivisionByZero,
+    // A number with a fractional part, when evaluating with integers
+    ExpectedInteger,
+}
+
+impl EvaluationError {
+    // Describe the error in English, e.g. "division by zero"
+    pub fn describe(&self) -> String {
+        match self {
+            EvaluationError::ExpectedNumber => "expected a number",
+            EvaluationError::ExpectedOperator => "expected an operator",
+            EvaluationError::DivisionByZero => "division by zero",
+            EvaluationError::ExpectedInteger => "expected an integer",
+        }
+        .to_string()
+    }
+}
+
+// An error lexing or evaluating a string taken from a token source
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SourceError {
+    Lexing(LexingError),
+    Evaluation(EvaluationError),
+}
+
+impl From<LexingError> for SourceError {
+    fn from(error: LexingError) -> Self {
+        SourceError::Lexing(error)
+    }
+}
+
+impl From<EvaluationError> for SourceError {
+    fn from(error: EvaluationError) -> Self {
+        SourceError::Evaluation(error)
+    }
+}
+
+// ====================
+// Arithmetics
+// ====================
+
+// The numbers an expression is evaluated with, and the operations on them
+trait Arithmetic {
+    type Number;
+
+    // The number of a token, an error if it isn't one
+    fn number(&self, token: &Token) -> Result<Self::Number, EvaluationError>;
+
+    fn apply(
+        &self,
+        left: Self::Number,
+        operator: OperatorKind,
+        right: Self::Number,
+    ) -> Result<Self::Number, EvaluationError>;
+}
+
+struct FloatArithmetic;
+
+impl Arithmetic for FloatArithmetic {
+    type Number = f64;
+
+    fn number(&self, token: &Token) -> Result<f64, EvaluationError> {
+        match token {
+            Token::Number(number) => Ok(*number),
+            Token::Decimal(decimal) => Ok(decimal.to_f64()),
+            Token::Integer(integer) => Ok(integer.to_string().parse().unwrap()),
+            _ => Err(EvaluationError::ExpectedNumber),
+        }
+    }
+
+    fn apply(&self, left: f64, operator: OperatorKind, right: f64) -> Result<f64, EvaluationError> {
+        match operator {
+            OperatorKind::Add => Ok(left + right),
+            OperatorKind::Subtract => Ok(left - right),
+            OperatorKind::Multiply => Ok(left * right),
+            OperatorKind::Divide if right == 0.0 => Err(EvaluationError::DivisionByZero),
+            OperatorKind::Divide => Ok(left / right),
+        }
+    }
+}
+
+// How quotients are rounded when evaluating with decimals
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DecimalContext {
+    // The number of decimals of every quotient
+    pub scale: u32,
+    pub rounding: Rounding,
+}
+
+impl Default for DecimalContext {
+    fn default() -> Self {
+        Self {
+            scale: 20,
+            rounding: Rounding::HalfEven,
+        }
+    }
+}
+
+impl Arithmetic for DecimalContext {
+    type Number = Decimal;
+
+    // Floats are converted from the shortest decimal that gives them back
+    fn number(&self, token: &Token) -> Result<Decimal, EvaluationError> {
+        match token {
+            Token::Decimal(decimal) => Ok(decimal.clone()),
+            Token::Integer(integer) => Ok(Decimal::new(integer.clone(), 0)),
+            Token::Number(number) if number.is_finite() => Ok(number.to_string().parse().unwrap()),
+            _ => Err(EvaluationError::ExpectedNumber),
+        }
+    }
+
+    fn apply(
+        &self,
+        left: Decimal,
+        operator: OperatorKind,
+        right: Decimal,
+    ) -> Result<Decimal, EvaluationError> {
+        match operator {
+            OperatorKind::Add => Ok(&left + &right),
+            OperatorKind::Subtract => Ok(&left - &right),
+            OperatorKind::Multiply => Ok(&left * &right),
+            OperatorKind::Divide => left
+                .checked_div(&right, self.scale, self.rounding)
+                .ok_or(EvaluationError::DivisionByZero),
+        }
+    }
+}
+
+// How the quotient of integers is rounded, and so which remainder goes with it.
+// In every case `dividend == quotient * divisor + remainder`, with `|remainder| < |divisor|`.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum IntegerDivision {
+    // Towards zero, the remainder has the sign of the dividend
+    Truncate,
+    // Towards negative infinity, the remainder has the sign of the divisor
+    Floor,
+    // The remainder is never negative
+    Euclid,
+}
+
+impl IntegerDivision {
+    // The quotient and the remainder, `None` when dividing by zero
+    pub fn div_rem(self, dividend: &BigInt, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.sign() == Sign::NoSign {
+            return None;
+        }
+
+        // The operators of `BigInt` truncate
+        let mut quotient = dividend / divisor;
+        let mut remainder = dividend % divisor;
+
+        match self {
+            IntegerDivision::Truncate => {}
+            IntegerDivision::Floor => {
+                if remainder.sign() != Sign::NoSign && remainder.sign() != divisor.sign() {
+                    quotient -= 1;
+                    remainder += divisor;
+                }
+            }
+            // Down for positive divisors, up for negative ones
+            IntegerDivision::Euclid => {
+                if remainder.sign() == Sign::Minus && divisor.sign() == Sign::Plus {
+                    quotient -= 1;
+                    remainder += divisor;
+                } else if remainder.sign() == Sign::Minus {
+                    quotient += 1;
+                    remainder -= divisor;
+                }
+            }
+        }
+
+        Some((quotient, remainder))
+    }
+}
+
+// How expressions are evaluated with integers
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct IntegerContext {
+    pub division: IntegerDivision,
+}
+
+impl Default for IntegerContext {
+    fn default() -> Self {
+        Self {
+            division: IntegerDivision::Truncate,
+        }
+    }
+}
+
+impl IntegerContext {
+    // The quotient, which `/` gives, and the remainder of dividing as the context says
+    pub fn div_rem(
+        &self,
+        dividend: &BigInt,
+        divisor: &BigInt,
+    ) -> Result<(BigInt, BigInt), EvaluationError> {
+        self.division
+            .div_rem(dividend, divisor)
+            .ok_or(EvaluationError::DivisionByZero)
+    }
+}
+
+impl Arithmetic for IntegerContext {
+    type Number = BigInt;
+
+    // Decimals and floats are taken when they have no fractional part
+    fn number(&self, token: &Token) -> Result<BigInt, EvaluationError> {
+        match token {
+            Token::Integer(integer) => Ok(integer.clone()),
+            Token::Decimal(decimal) if decimal.normalize().scale() == 0 => {
+                Ok(decimal.normalize().mantissa().clone())
+            }
+            Token::Number(number) if number.fract() == 0.0 => {
+                Ok(format!("{:.0}", number).parse().unwrap())
+            }
+            Token::Decimal(_) | Token::Number(_) => Err(EvaluationError::ExpectedInteger),
+            _ => Err(EvaluationError::ExpectedNumber),
+        }
+    }
+
+    fn apply(
+        &self,
+        left: BigInt,
+        operator: OperatorKind,
+        right: BigInt,
+    ) -> Result<BigInt, EvaluationError> {
+        match operator {
+            OperatorKind::Add => Ok(left + right),
+            OperatorKind::Subtract => Ok(left - right),
+            OperatorKind::Multiply => Ok(left * right),
+            OperatorKind::Divide => Ok(self.div_rem(&left, &right)?.0),
+        }
+    }
+}
+
+// ====================
+// Evaluation
+// ====================
+
+fn next_number<'a, A: Arithmetic>(
+    arithmetic: &A,
+    tokens: &mut impl Iterator<Item = &'a Token>,
+) -> Result<A::Number, EvaluationError> {
+    match tokens.next() {
+        Some(token) => arithmetic.number(token),
+        None => Err(EvaluationError::ExpectedNumber),
+    }
+}
+
+// Multiplications and divisions first, from left to right
+fn evaluate_with<A: Arithmetic>(
+    arithmetic: &A,
+    tokens: &[Token],
+) -> Result<A::Number, EvaluationError> {
+    let tokens = match tokens.split_last() {
+        Some((Token::EndOfExpression, tokens)) => tokens,
+        _ => tokens,
+    };
+    let mut tokens = tokens.iter();
+
+    // The sum so far with the operator adding the current term to it, none for the first term
+    let mut total: Option<(A::Number, OperatorKind)> = None;
+    let mut term = next_number(arithmetic, &mut tokens)?;
+
+    while let Some(token) = tokens.next() {
+        let operator = match token {
+            Token::Operator(operator) => *operator,
+            _ => return Err(EvaluationError::ExpectedOperator),
+        };
+        let number = next_number(arithmetic, &mut tokens)?;
+
+        match operator {
+            OperatorKind::Multiply | OperatorKind::Divide => {
+                term = arithmetic.apply(term, operator, number)?;
+            }
+            OperatorKind::Add | OperatorKind::Subtract => {
+                let sum = match total {
+                    Some((total, sign)) => arithmetic.apply(total, sign, term)?,
+                    None => term,
+                };
+                total = Some((sum, operator));
+                term = number;
+            }
+        }
+    }
+
+    match total {
+        Some((total, sign)) => arithmetic.apply(total, sign, term),
+        None => Ok(term),
+    }
+}
+
+// Evaluate the tokens of a single expression, multiplications and divisions first, from left
+// to right. A trailing `Token::EndOfExpression` is ignored.
+pub fn evaluate(tokens: &[Token]) -> Result<f64, EvaluationError> {
+    evaluate_with(&FloatArithmetic, tokens)
+}
+
+// Evaluate the tokens exactly, rounding quotients as the context says. Numbers are best lexed
+// as `Token::Decimal`, floats are taken as the shortest decimal that gives them back.
+pub fn evaluate_decimal(
+    tokens: &[Token],
+    context: &DecimalContext,
+) -> Result<Decimal, EvaluationError> {
+    evaluate_with(context, tokens)
+}
+
+// Evaluate the tokens with integers of any size, dividing as the context says. Numbers are best
+// lexed with `NumberRepresentation::Exact`, the others must have no fractional part.
+pub fn evaluate_integer(
+    tokens: &[Token],
+    context: &IntegerContext,
+) -> Result<BigInt, EvaluationError> {
+    evaluate_with(context, tokens)
+}
+
+// ====================
+// Evaluation of token sources
+// ====================
+
+// Lex the string with the source, then evaluate its tokens
+fn evaluate_source_with<A: Arithmetic>(
+    arithmetic: &A,
+    source: &mut impl TokenSource,
+    string: &str,
+) -> Result<A::Number, SourceError> {
+    let tokens = source.tokenize(string)?;
+    Ok(evaluate_with(arithmetic, &tokens)?)
+}
+
+// Same as `evaluate`, with the tokens of the string lexed by any lexer implementation
+pub fn evaluate_source(source: &mut impl TokenSource, string: &str) -> Result<f64, SourceError> {
+    evaluate_source_with(&FloatArithmetic, source, string)
+}
+
+// Same as `evaluate_decimal`, with the tokens of the string lexed by any lexer implementation
+pub fn evaluate_decimal_source(
+    source: &mut impl TokenSource,
+    string: &str,
+    context: &DecimalContext,
+) -> Result<Decimal, SourceError> {
+    evaluate_source_with(context, source, string)
+}
+
+// Same as `evaluate_integer`, with the tokens of the string lexed by any lexer implementation
+pub fn evaluate_integer_source(
+    source: &mut impl TokenSource,
+    string: &str,
+    context: &IntegerContext,
+) -> Result<BigInt, SourceError> {
+    evaluate_source_with(context, source, string)
+}
@@ -0,0 +1,292 @@
+use num_bigint::BigInt;
+use rand::Rng;
+
+use crate::decimal::Decimal;
+use crate::evaluate::{
+    evaluate, evaluate_decimal, evaluate_decimal_source, evaluate_integer, evaluate_integer_source,
+    evaluate_source, DecimalContext, EvaluationError, IntegerContext, IntegerDivision, SourceError,
+};
+use crate::seeded;
+use crate::spec_lexer::generated_spec;
+use crate::token_source::TokenSource;
+use crate::tokenize::{
+    tokenize, tokenize_with, Backend, ExpressionLexingError, Lexer, LexingError,
+    NumberRepresentation, OperatorKind, Token,
+};
+
+fn evaluate_string(string: &str) -> Result<f64, EvaluationError> {
+    evaluate(&tokenize(string).unwrap())
+}
+
+#[test]
+fn test_evaluate_single_number() {
+    assert_eq!(evaluate_string("0.5"), Ok(0.5));
+}
+
+#[test]
+fn test_evaluate_precedence() {
+    assert_eq!(evaluate_string("1 + 2 * 3"), Ok(7.0));
+    assert_eq!(evaluate_string("2 * 3 + 1"), Ok(7.0));
+    assert_eq!(evaluate_string("1 - 8 / 4 * 3 + 2"), Ok(-3.0));
+}
+
+#[test]
+fn test_evaluate_left_to_right() {
+    assert_eq!(evaluate_string("10 - 4 - 3"), Ok(3.0));
+    assert_eq!(evaluate_string("64 / 4 / 2"), Ok(8.0));
+}
+
+#[test]
+fn test_evaluate_division_by_zero() {
+    assert_eq!(
+        evaluate_string("1 + 2 / 0"),
+        Err(EvaluationError::DivisionByZero)
+    );
+    assert_eq!(
+        evaluate_string("1 / 0.0"),
+        Err(EvaluationError::DivisionByZero)
+    );
+}
+
+#[test]
+fn test_evaluate_end_of_expression() {
+    let tokens = vec![
+        Token::Number(1.0),
+        Token::Operator(OperatorKind::Add),
+        Token::Number(2.0),
+        Token::EndOfExpression,
+    ];
+
+    assert_eq!(evaluate(&tokens), Ok(3.0));
+    assert_eq!(evaluate(&tokens[1..]), Err(EvaluationError::ExpectedNumber));
+}
+
+#[test]
+fn test_evaluate_malformed_tokens() {
+    assert_eq!(evaluate(&[]), Err(EvaluationError::ExpectedNumber));
+    assert_eq!(
+        evaluate(&[Token::Number(1.0), Token::Operator(OperatorKind::Add)]),
+        Err(EvaluationError::ExpectedNumber)
+    );
+    assert_eq!(
+        evaluate(&[Token::Number(1.0), Token::Number(2.0)]),
+        Err(EvaluationError::ExpectedOperator)
+    );
+    assert_eq!(
+        evaluate(&[
+            Token::Number(1.0),
+            Token::EndOfExpression,
+            Token::Number(2.0)
+        ]),
+        Err(EvaluationError::ExpectedOperator)
+    );
+}
+
+fn tokenize_exact(string: &str) -> Vec<Token> {
+    tokenize_with(
+        &mut Lexer::with_numbers(NumberRepresentation::Exact),
+        string,
+    )
+    .unwrap()
+}
+
+fn evaluate_integer_string(
+    string: &str,
+    context: &IntegerContext,
+) -> Result<String, EvaluationError> {
+    evaluate_integer(&tokenize_exact(string), context).map(|value| value.to_string())
+}
+
+fn integer(string: &str) -> BigInt {
+    string.parse().unwrap()
+}
+
+#[test]
+fn test_exact_tokens() {
+    assert_eq!(
+        tokenize_exact("123456789012345678901234567890 * 0.50"),
+        vec![
+            Token::Integer(integer("123456789012345678901234567890")),
+            Token::Operator(OperatorKind::Multiply),
+            Token::Decimal("0.50".parse().unwrap()),
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_integer() {
+    let context = IntegerContext::default();
+
+    assert_eq!(
+        evaluate_integer_string(
+            "123456789012345678901234567890 * 1000000000000 + 1",
+            &context
+        ),
+        Ok("123456789012345678901234567890000000000001".to_string())
+    );
+    assert_eq!(
+        evaluate_integer_string("1 - 99999999999999999999 * 3", &context),
+        Ok("-299999999999999999996".to_string())
+    );
+    assert_eq!(
+        evaluate_integer_string("1 - 8 / 3 * 3 + 2", &context),
+        Ok("-3".to_string())
+    );
+    assert_eq!(
+        evaluate_integer_string("1 / 0", &context),
+        Err(EvaluationError::DivisionByZero)
+    );
+    assert_eq!(
+        evaluate_integer_string("1 + 0.5", &context),
+        Err(EvaluationError::ExpectedInteger)
+    );
+
+    // Whole decimals and floats are integers
+    assert_eq!(
+        evaluate_integer(&tokenize("7 / 2 + 0.0").unwrap(), &context),
+        Ok(BigInt::from(3))
+    );
+
+    // The remainder is taken from the context, `/` always gives the quotient
+    let dividend = evaluate_integer(&tokenize_exact("100000000000000000000 + 1"), &context);
+    assert_eq!(
+        context.div_rem(&dividend.unwrap(), &integer("7")),
+        Ok((integer("14285714285714285714"), integer("3")))
+    );
+    assert_eq!(
+        context.div_rem(&integer("7"), &integer("0")),
+        Err(EvaluationError::DivisionByZero)
+    );
+}
+
+#[test]
+fn test_exact_tokens_in_other_evaluations() {
+    let tokens = tokenize_exact("123456789012345678901234567890 + 0.5");
+
+    assert_eq!(evaluate(&tokens), Ok(123456789012345678901234567890.5));
+    assert_eq!(
+        evaluate_decimal(&tokens, &DecimalContext::default()),
+        Ok("123456789012345678901234567890.5"
+            .parse::<Decimal>()
+            .unwrap())
+    );
+}
+
+#[test]
+fn test_integer_division() {
+    use IntegerDivision::*;
+
+    // The quotient and remainder of every division, for every sign
+    let cases = [
+        (Truncate, [(2, 1), (-2, -1), (-2, 1), (2, -1)]),
+        (Floor, [(2, 1), (-3, 2), (-3, -2), (2, -1)]),
+        (Euclid, [(2, 1), (-3, 2), (-2, 1), (3, 2)]),
+    ];
+    let operands = [(7, 3), (-7, 3), (7, -3), (-7, -3)];
+
+    for (division, expected) in cases.iter() {
+        for ((dividend, divisor), (quotient, remainder)) in operands.iter().zip(expected) {
+            assert_eq!(
+                division.div_rem(&BigInt::from(*dividend), &BigInt::from(*divisor)),
+                Some((BigInt::from(*quotient), BigInt::from(*remainder))),
+                "\n {} / {} with {:?}\n\n",
+                dividend,
+                divisor,
+                division
+            );
+        }
+        assert_eq!(division.div_rem(&BigInt::from(1), &BigInt::from(0)), None);
+    }
+}
+
+/// An integer of up to 40 digits, of either sign
+fn random_integer() -> String {
+    let mut rng = seeded::thread_rng();
+    let digits: String = (0..rng.gen_range(1..=40))
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect();
+    let sign = if rng.gen_bool(0.5) { "-" } else { "" };
+    format!("{}{}", sign, digits)
+}
+
+#[test]
+fn test_integer_division_random() {
+    seeded::check(
+        2000,
+        || (random_integer(), random_integer()),
+        |(dividend, divisor)| {
+            // Shrinking can leave something else than integers
+            let (dividend, divisor) = match (dividend.parse::<BigInt>(), divisor.parse::<BigInt>())
+            {
+                (Ok(dividend), Ok(divisor)) => (dividend, divisor),
+                _ => return,
+            };
+
+            for division in [
+                IntegerDivision::Truncate,
+                IntegerDivision::Floor,
+                IntegerDivision::Euclid,
+            ] {
+                let (quotient, remainder) = match division.div_rem(&dividend, &divisor) {
+                    Some(result) => result,
+                    None => continue,
+                };
+
+                assert_eq!(&quotient * &divisor + &remainder, dividend);
+                assert!(remainder.magnitude() < divisor.magnitude());
+            }
+        },
+    );
+}
+
+/// Evaluate the string with every lexer implementation, checking they all give the same result
+fn evaluate_with_every_source(string: &str) -> Result<f64, SourceError> {
+    let values = [
+        evaluate_source(&mut tokenize, string),
+        evaluate_source(&mut Lexer::new(), string),
+        evaluate_source(&mut Lexer::with_backend(Backend::TableDriven), string),
+        evaluate_source(&mut generated_spec().clone(), string),
+    ];
+
+    for value in values.iter() {
+        assert_eq!(*value, values[0], "\n input: {:?}\n\n", string);
+    }
+    values[0]
+}
+
+#[test]
+fn test_evaluate_source() {
+    assert_eq!(evaluate_with_every_source("1 - 8 / 4 * 3 + 2"), Ok(-3.0));
+    assert_eq!(evaluate_with_every_source("0.5*4"), Ok(2.0));
+    assert_eq!(
+        evaluate_with_every_source("1 +"),
+        Err(SourceError::Lexing(LexingError::IncorrectExpression(
+            ExpressionLexingError::ExpectedNumber
+        )))
+    );
+    assert_eq!(
+        evaluate_with_every_source("1 / 0"),
+        Err(SourceError::Evaluation(EvaluationError::DivisionByZero))
+    );
+
+    // Exact numbers need a lexer emitting them
+    let mut lexer = Lexer::with_numbers(NumberRepresentation::Exact);
+    assert_eq!(
+        evaluate_integer_source(
+            &mut lexer,
+            "99999999999999999999 + 1",
+            &IntegerContext::default()
+        ),
+        Ok(integer("100000000000000000000"))
+    );
+    assert_eq!(
+        evaluate_decimal_source(&mut lexer, "0.1 + 0.2", &DecimalContext::default()),
+        Ok("0.3".parse::<Decimal>().unwrap())
+    );
+
+    // The lexer is reused
+    assert_eq!(
+        lexer.tokenize("7"),
+        Ok(vec![Token::Integer(BigInt::from(7))])
+    );
+}
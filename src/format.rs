@@ -0,0 +1,64 @@
+use crate::lossless::{tokenize_lossless, LosslessToken};
+use crate::tokenize::LexingError;
+
+// Canonical expressions have a single space around every operator and numbers without
+// trailing zeros. The grammar has no parentheses, so there are never any to remove.
+
+// An error in a line of a text, counted from 1
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LineError {
+    pub line: usize,
+    pub error: LexingError,
+}
+
+// Remove the trailing zeros of a decimal lexeme, and its point if nothing is left after it.
+// Numbers are normalized from their text, so that long ones keep all their digits.
+pub fn normalize_number(lexeme: &str) -> String {
+    match lexeme.split_once('.') {
+        Some((integer, decimals)) => match decimals.trim_end_matches('0') {
+            "" => integer.to_string(),
+            decimals => format!("{}.{}", integer, decimals),
+        },
+        None => lexeme.to_string(),
+    }
+}
+
+// Format a single expression
+pub fn format_expression(input: &str) -> Result<String, LexingError> {
+    let tokens = tokenize_lossless(input)?;
+
+    let words: Vec<String> = tokens
+        .iter()
+        .filter_map(|token| match token {
+            LosslessToken::Number { text, .. } => Some(normalize_number(text)),
+            LosslessToken::Operator { text, .. } => Some(text.clone()),
+            LosslessToken::Whitespace(_) => None,
+        })
+        .collect();
+
+    Ok(words.join(" "))
+}
+
+// Format every line of the text as an expression. Blank lines are emptied, and every line
+// ends with "\n".
+pub fn format_text(text: &str) -> Result<String, LineError> {
+    let mut formatted = String::with_capacity(text.len());
+
+    for (index, line) in text.lines().enumerate() {
+        if !line.trim().is_empty() {
+            let line = format_expression(line).map_err(|error| LineError {
+                line: index + 1,
+                error,
+            })?;
+            formatted.push_str(&line);
+        }
+        formatted.push('\n');
+    }
+
+    Ok(formatted)
+}
+
+// Whether the text is already formatted
+pub fn check_text(text: &str) -> Result<bool, LineError> {
+    Ok(format_text(text)? == text)
+}
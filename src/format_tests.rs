@@ -0,0 +1,80 @@
+use crate::differential::random_input;
+use crate::format::{check_text, format_expression, format_text, normalize_number, LineError};
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{tokenize, ExpressionLexingError, LexingError};
+
+#[test]
+fn test_canonical_spacing() {
+    assert_eq!(format_expression("1+ 2 *3"), Ok("1 + 2 * 3".to_string()));
+    assert_eq!(
+        format_expression("   12/0.5-  7   "),
+        Ok("12 / 0.5 - 7".to_string())
+    );
+    assert_eq!(format_expression("42"), Ok("42".to_string()));
+}
+
+#[test]
+fn test_normalize_number() {
+    assert_eq!(normalize_number("0.50"), "0.5");
+    assert_eq!(normalize_number("0.000"), "0");
+    assert_eq!(normalize_number("0.0625"), "0.0625");
+    assert_eq!(normalize_number("100"), "100");
+    assert_eq!(normalize_number("0"), "0");
+    assert_eq!(
+        normalize_number("0.12345678901234567890123000"),
+        "0.12345678901234567890123"
+    );
+}
+
+#[test]
+fn test_format_errors() {
+    assert_eq!(
+        format_expression("1 2"),
+        Err(LexingError::IncorrectExpression(
+            ExpressionLexingError::ExpectedOperator
+        ))
+    );
+    assert_eq!(
+        format_text("1 + 1\n\n2 +\n"),
+        Err(LineError {
+            line: 3,
+            error: LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber),
+        })
+    );
+}
+
+#[test]
+fn test_format_text() {
+    assert_eq!(
+        format_text("1+2\r\n   \n0.10 *3"),
+        Ok("1 + 2\n\n0.1 * 3\n".to_string())
+    );
+}
+
+#[test]
+fn test_check_text() {
+    assert_eq!(check_text("1 + 2\n\n0.1 * 3\n"), Ok(true));
+    assert_eq!(check_text(""), Ok(true));
+    assert_eq!(check_text("1 + 2"), Ok(false));
+    assert_eq!(check_text("1 +2\n"), Ok(false));
+    assert_eq!(check_text("0.50\n"), Ok(false));
+}
+
+#[test]
+fn test_format_random() {
+    seeded::check(
+        5000,
+        || random_input(&ALPHABET, 30),
+        |input| {
+            match format_expression(input) {
+                // Formatting keeps the tokens, and formatting again changes nothing
+                Ok(formatted) => {
+                    assert_eq!(tokenize(&formatted), tokenize(input));
+                    assert_eq!(format_expression(&formatted), Ok(formatted.clone()));
+                }
+                Err(error) => assert_eq!(Err(error), tokenize(input)),
+            }
+        },
+    );
+}
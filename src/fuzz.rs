@@ -0,0 +1,221 @@
+use std::io::{self, BufReader, Read};
+
+use crate::evaluate::{evaluate, EvaluationError};
+use crate::lossless::{to_text, to_tokens, tokenize_lossless};
+use crate::reader::{tokenize_lines, tokenize_reader, ReadError};
+use crate::spec_lexer::{generated_spec, tokenize_with_spec};
+use crate::tokenize::{tokenize, tokenize_with, Backend, Lexer, LexingError, OperatorKind, Token};
+
+// The fuzz targets, shared by the cargo-fuzz crate in `fuzz/` and the tests replaying its seeds.
+// Every target takes arbitrary bytes and panics if an invariant doesn't hold.
+
+// ====================
+// Helpers
+// ====================
+
+// A reader returning at most `size` bytes at a time
+struct ChunkedReader<'a> {
+    data: &'a [u8],
+    size: usize,
+}
+
+impl Read for ChunkedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.size.min(buf.len()).min(self.data.len());
+        buf[..read].copy_from_slice(&self.data[..read]);
+        self.data = &self.data[read..];
+        Ok(read)
+    }
+}
+
+fn read_tokens(reader: impl Read) -> Result<Vec<Token>, LexingError> {
+    tokenize_reader(reader)
+        .collect::<Result<Vec<Token>, ReadError>>()
+        .map_err(|error| match error {
+            ReadError::Lexing(error) => error,
+            ReadError::Io(error) => panic!("reading bytes failed: {}", error),
+        })
+}
+
+fn read_lines(reader: impl io::BufRead) -> Vec<String> {
+    tokenize_lines(reader)
+        .map(|line| format!("{:?}", line))
+        .collect()
+}
+
+// Write the tokens back as an expression, or `None` if a number can't be written
+fn render(tokens: &[Token]) -> Option<String> {
+    let strings: Option<Vec<String>> = tokens
+        .iter()
+        .map(|token| match token {
+            Token::Number(number) if number.is_finite() => Some(number.to_string()),
+            Token::Number(_) => None,
+            Token::Decimal(decimal) => Some(decimal.to_string()),
+            Token::Integer(integer) => Some(integer.to_string()),
+            Token::Operator(OperatorKind::Add) => Some("+".to_string()),
+            Token::Operator(OperatorKind::Subtract) => Some("-".to_string()),
+            Token::Operator(OperatorKind::Multiply) => Some("*".to_string()),
+            Token::Operator(OperatorKind::Divide) => Some("/".to_string()),
+            Token::EndOfExpression => None,
+        })
+        .collect();
+
+    strings.map(|strings| strings.join(" "))
+}
+
+fn same_value(a: Result<f64, EvaluationError>, b: Result<f64, EvaluationError>) -> bool {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a == b || (a.is_nan() && b.is_nan()),
+        (a, b) => a == b,
+    }
+}
+
+// ====================
+// Targets
+// ====================
+
+// Lex the bytes with every lexer, which should agree, and lex the tokens written back.
+// Lossless tokens should give back the input.
+pub fn fuzz_lexer(data: &[u8]) {
+    let expected = read_tokens(data);
+
+    let string = match std::str::from_utf8(data) {
+        Ok(string) => string,
+        Err(_) => {
+            assert!(expected.is_err(), "invalid UTF-8 was lexed: {:?}", data);
+            return;
+        }
+    };
+
+    assert_eq!(tokenize(string), expected, "input: {:?}", string);
+    assert_eq!(
+        tokenize_with(&mut Lexer::with_backend(Backend::TableDriven), string),
+        expected,
+        "input: {:?}",
+        string
+    );
+    assert_eq!(
+        tokenize_with_spec(generated_spec(), string),
+        expected,
+        "input: {:?}",
+        string
+    );
+
+    let lossless = tokenize_lossless(string);
+    if let Ok(tokens) = &lossless {
+        assert_eq!(to_text(tokens), string);
+    }
+    assert_eq!(
+        lossless.map(|tokens| to_tokens(&tokens)),
+        expected,
+        "input: {:?}",
+        string
+    );
+
+    if let Some(rendered) = expected.as_ref().ok().and_then(|tokens| render(tokens)) {
+        assert_eq!(tokenize(&rendered), expected, "input: {:?}", string);
+    }
+}
+
+// Feed the bytes after the first one in chunks sized by the first one, which should give the
+// same tokens as feeding them at once, including through a snapshot at every chunk boundary
+pub fn fuzz_chunks(data: &[u8]) {
+    let (size, data) = match data.split_first() {
+        Some((&first, data)) => (usize::from(first % 16) + 1, data),
+        None => return,
+    };
+    let chunked = || ChunkedReader { data, size };
+
+    assert_eq!(
+        read_tokens(chunked()),
+        read_tokens(data),
+        "input: {:?}",
+        data
+    );
+    assert_eq!(
+        read_lines(BufReader::with_capacity(size, chunked())),
+        read_lines(data),
+        "input: {:?}",
+        data
+    );
+
+    let string = match std::str::from_utf8(data) {
+        Ok(string) => string,
+        Err(_) => return,
+    };
+    let chars: Vec<char> = string.chars().collect();
+
+    let mut lexer = Lexer::new();
+    let mut tokens = vec![];
+    let mut result = Ok(());
+    for chunk in chars.chunks(size) {
+        lexer = Lexer::from_snapshot(&lexer.snapshot()).expect("snapshots can be resumed");
+        for &c in chunk {
+            match lexer.feed(Some(c)) {
+                Ok(mut emitted) => tokens.append(&mut emitted),
+                Err(error) => result = result.and(Err(error)),
+            }
+        }
+    }
+    match lexer.feed(None) {
+        Ok(mut emitted) => tokens.append(&mut emitted),
+        Err(error) => result = result.and(Err(error)),
+    }
+
+    assert_eq!(
+        result.map(|_| tokens),
+        tokenize(string),
+        "input: {:?}",
+        string
+    );
+}
+
+// Evaluate the tokens of the bytes, and a token sequence built from the bytes
+pub fn fuzz_evaluate(data: &[u8]) {
+    let string = String::from_utf8_lossy(data);
+    if let Ok(tokens) = tokenize(&string) {
+        let value = evaluate(&tokens);
+        assert!(
+            !matches!(
+                value,
+                Err(EvaluationError::ExpectedNumber | EvaluationError::ExpectedOperator)
+            ),
+            "input: {:?}",
+            string
+        );
+
+        if let Some(rendered) = render(&tokens) {
+            let tokens = tokenize(&rendered).expect("written tokens can be lexed");
+            assert!(same_value(evaluate(&tokens), value), "input: {:?}", string);
+        }
+    }
+
+    // Numbers at even positions and operators at odd ones make a well formed sequence
+    let tokens: Vec<Token> = data
+        .iter()
+        .map(|&byte| match byte % 5 {
+            0 => Token::Operator(OperatorKind::Add),
+            1 => Token::Operator(OperatorKind::Subtract),
+            2 => Token::Operator(OperatorKind::Multiply),
+            3 => Token::Operator(OperatorKind::Divide),
+            _ => Token::Number(f64::from(byte / 5)),
+        })
+        .collect();
+    let well_formed = tokens.len() % 2 == 1
+        && tokens
+            .iter()
+            .enumerate()
+            .all(|(i, token)| matches!(token, Token::Number(_)) == (i % 2 == 0));
+
+    // Evaluation stops at the first error, which can be a division before a malformed part
+    let value = evaluate(&tokens);
+    let malformed = matches!(
+        value,
+        Err(EvaluationError::ExpectedNumber | EvaluationError::ExpectedOperator)
+    );
+    if well_formed {
+        assert!(!malformed, "tokens: {:?}", tokens);
+    } else {
+        assert!(value.is_err(), "tokens: {:?}", tokens);
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::Path;
+
+use rand::Rng;
+
+use crate::fuzz::{fuzz_chunks, fuzz_evaluate, fuzz_lexer};
+use crate::seeded;
+
+/// Bytes of every input class, of multi-byte characters and of invalid UTF-8
+const BYTES: [u8; 16] = [
+    b'0', b'1', b'9', b'.', b' ', b'+', b'-', b'*', b'/', b'x', b'\n', b'\r', 0xc3, 0xa9, 0xff,
+    0x80,
+];
+
+/// The number and length of random inputs
+const RANDOM_COUNT: u32 = 5000;
+const RANDOM_LEN: usize = 40;
+
+/// Run the target over the seeds checked in for cargo-fuzz
+fn run_seeds(target: &str, fuzz: impl Fn(&[u8])) {
+    let seeds = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/seeds")
+        .join(target);
+
+    let mut count = 0;
+    for entry in fs::read_dir(seeds).unwrap() {
+        fuzz(&fs::read(entry.unwrap().path()).unwrap());
+        count += 1;
+    }
+    assert!(count > 0);
+}
+
+/// Random bytes, mostly from `BYTES`
+fn random_bytes() -> Vec<u8> {
+    let mut rng = seeded::thread_rng();
+    let len = rng.gen_range(0..RANDOM_LEN);
+
+    (0..len)
+        .map(|_| {
+            if rng.gen_bool(0.9) {
+                BYTES[rng.gen_range(0..BYTES.len())]
+            } else {
+                rng.gen()
+            }
+        })
+        .collect()
+}
+
+/// Run the target over random bytes
+fn run_random(fuzz: impl Fn(&[u8])) {
+    seeded::check(RANDOM_COUNT, random_bytes, |data| fuzz(data));
+}
+
+#[test]
+fn test_lexer_seeds() {
+    run_seeds("lexer", fuzz_lexer);
+}
+
+#[test]
+fn test_lexer_random() {
+    run_random(fuzz_lexer);
+}
+
+#[test]
+fn test_chunks_seeds() {
+    run_seeds("chunks", fuzz_chunks);
+}
+
+#[test]
+fn test_chunks_random() {
+    run_random(fuzz_chunks);
+}
+
+#[test]
+fn test_evaluate_seeds() {
+    run_seeds("evaluate", fuzz_evaluate);
+}
+
+#[test]
+fn test_evaluate_random() {
+    run_random(fuzz_evaluate);
+}
@@ -0,0 +1,344 @@
+// Golden test cases, one `.case` file per input:
+//
+//     # Comments, kept when blessing
+//     input "0.5 * 12"
+//     number 0.5
+//     operator *
+//     number 12
+//
+// The expected tokens follow the input, one per line, or a single error with the name of the
+// lexing error and the span of the input it was raised at, in bytes:
+//
+//     input "1 +"
+//     error ExpectedNumber 3..3
+//
+// Strings use the escapes of lexer specifications: `\\`, `\"`, `\n`, `\r` and `\t`.
+
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::incremental::{tokenize_spanned_with, SpannedToken};
+use crate::spec::{split_words, ErrorName, Word};
+use crate::spec_lexer::to_lexing_error;
+use crate::tokenize::{ExpressionLexingError, Lexer, LexingError, OperatorKind, Token};
+
+pub const CASE_EXTENSION: &str = "case";
+
+// ====================
+// Cases
+// ====================
+
+// A lexing error, with the bytes of the input it was raised at
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedError {
+    pub error: LexingError,
+    // Empty at the end of input
+    pub span: Range<usize>,
+}
+
+pub type CaseOutput = Result<Vec<Token>, SpannedError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub comments: Vec<String>,
+    pub input: String,
+    pub expected: CaseOutput,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CaseErrorKind {
+    InvalidLine,
+    InvalidString,
+    MissingInput,
+    DuplicateInput,
+    ExpectationBeforeInput,
+    InvalidNumber(String),
+    UnknownOperator(String),
+    UnknownError(String),
+    InvalidSpan(String),
+    // Either tokens or a single error are expected
+    TokensAndError,
+}
+
+// Line numbers start at 1, line 0 is used for errors about the whole case
+#[derive(Clone, PartialEq, Debug)]
+pub struct CaseError {
+    pub line: usize,
+    pub kind: CaseErrorKind,
+}
+
+// Tokenize the input, locating the error if any
+pub fn run_case(input: &str) -> CaseOutput {
+    tokenize_spanned_with(Lexer::new(), input)
+        .into_iter()
+        .map(|SpannedToken { token, span }| token.map_err(|error| SpannedError { error, span }))
+        .collect()
+}
+
+// ====================
+// Parsing
+// ====================
+
+fn operator_kind(name: &str) -> Option<OperatorKind> {
+    match name {
+        "+" => Some(OperatorKind::Add),
+        "-" => Some(OperatorKind::Subtract),
+        "*" => Some(OperatorKind::Multiply),
+        "/" => Some(OperatorKind::Divide),
+        _ => None,
+    }
+}
+
+fn operator_name(operator: OperatorKind) -> &'static str {
+    match operator {
+        OperatorKind::Add => "+",
+        OperatorKind::Subtract => "-",
+        OperatorKind::Multiply => "*",
+        OperatorKind::Divide => "/",
+    }
+}
+
+fn error_name(error: LexingError) -> String {
+    match error {
+        LexingError::IncorrectNumber(error) => format!("{:?}", error),
+        LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter(_)) => {
+            format!("{:?}", ErrorName::UnexpectedCharacter)
+        }
+        LexingError::IncorrectExpression(error) => format!("{:?}", error),
+        LexingError::IncorrectEncoding(error) => format!("{:?}", error),
+    }
+}
+
+fn parse_span(span: &str) -> Option<Range<usize>> {
+    let (start, end) = span.split_once("..")?;
+    let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+
+    if start <= end {
+        Some(start..end)
+    } else {
+        None
+    }
+}
+
+// The error raised for the span of the input
+fn parse_error(input: &str, name: &str, span: &str) -> Result<SpannedError, CaseErrorKind> {
+    let error =
+        ErrorName::from_name(name).ok_or_else(|| CaseErrorKind::UnknownError(name.to_string()))?;
+    let invalid_span = || CaseErrorKind::InvalidSpan(span.to_string());
+    let span = parse_span(span).ok_or_else(invalid_span)?;
+
+    // The span covers a single character, or nothing at the end of input
+    let c = match input.get(span.clone()) {
+        Some("") if span.start == input.len() => None,
+        Some(string) if string.chars().count() == 1 => string.chars().next(),
+        _ => return Err(invalid_span()),
+    };
+    if error == ErrorName::UnexpectedCharacter && c.is_none() {
+        return Err(invalid_span());
+    }
+
+    Ok(SpannedError {
+        error: to_lexing_error(error, c),
+        span,
+    })
+}
+
+pub fn parse_case(source: &str) -> Result<Case, CaseError> {
+    let mut comments = vec![];
+    let mut input: Option<String> = None;
+    let mut tokens = vec![];
+    let mut error: Option<SpannedError> = None;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let case_error = |kind| CaseError { line, kind };
+
+        let words = split_words(text).map_err(|_| case_error(CaseErrorKind::InvalidString))?;
+        if let Some(comment) = text.trim_start().strip_prefix('#') {
+            comments.push(comment.strip_prefix(' ').unwrap_or(comment).to_string());
+            continue;
+        }
+
+        let expected_input = || {
+            input
+                .as_deref()
+                .ok_or_else(|| case_error(CaseErrorKind::ExpectationBeforeInput))
+        };
+        if error.is_some() && !words.is_empty() {
+            return Err(case_error(CaseErrorKind::TokensAndError));
+        }
+
+        match words.as_slice() {
+            [] => {}
+            [Word::Bare(keyword), Word::Quoted(string)] if keyword == "input" => {
+                if input.is_some() {
+                    return Err(case_error(CaseErrorKind::DuplicateInput));
+                }
+                input = Some(string.clone());
+            }
+            [Word::Bare(keyword), Word::Bare(number)] if keyword == "number" => {
+                expected_input()?;
+                let number = number
+                    .parse()
+                    .map_err(|_| case_error(CaseErrorKind::InvalidNumber(number.clone())))?;
+                tokens.push(Token::Number(number));
+            }
+            [Word::Bare(keyword), Word::Bare(operator)] if keyword == "operator" => {
+                expected_input()?;
+                let operator = operator_kind(operator)
+                    .ok_or_else(|| case_error(CaseErrorKind::UnknownOperator(operator.clone())))?;
+                tokens.push(Token::Operator(operator));
+            }
+            [Word::Bare(keyword), Word::Bare(name), Word::Bare(span)] if keyword == "error" => {
+                let input = expected_input()?;
+                if !tokens.is_empty() {
+                    return Err(case_error(CaseErrorKind::TokensAndError));
+                }
+                error = Some(parse_error(input, name, span).map_err(case_error)?);
+            }
+            _ => return Err(case_error(CaseErrorKind::InvalidLine)),
+        }
+    }
+
+    let input = input.ok_or(CaseError {
+        line: 0,
+        kind: CaseErrorKind::MissingInput,
+    })?;
+
+    Ok(Case {
+        comments,
+        input,
+        expected: match error {
+            Some(error) => Err(error),
+            None => Ok(tokens),
+        },
+    })
+}
+
+// ====================
+// Writing
+// ====================
+
+fn quote(string: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl Case {
+    // Write the case in the `.case` format
+    pub fn to_source(&self) -> String {
+        let mut source = String::new();
+
+        for comment in self.comments.iter() {
+            writeln!(source, "# {}", comment).unwrap();
+        }
+        writeln!(source, "input {}", quote(&self.input)).unwrap();
+
+        match &self.expected {
+            Ok(tokens) => {
+                for token in tokens.iter() {
+                    match token {
+                        Token::Number(number) => writeln!(source, "number {}", number),
+                        Token::Decimal(decimal) => writeln!(source, "number {}", decimal),
+                        Token::Integer(integer) => writeln!(source, "number {}", integer),
+                        Token::Operator(operator) => {
+                            writeln!(source, "operator {}", operator_name(*operator))
+                        }
+                        // Never emitted when lexing a single expression
+                        Token::EndOfExpression => Ok(()),
+                    }
+                    .unwrap();
+                }
+            }
+            Err(SpannedError { error, span }) => {
+                writeln!(
+                    source,
+                    "error {} {}..{}",
+                    error_name(*error),
+                    span.start,
+                    span.end
+                )
+                .unwrap();
+            }
+        }
+
+        source
+    }
+}
+
+// ====================
+// Running a directory
+// ====================
+
+#[derive(Debug, PartialEq)]
+pub enum CaseResult {
+    Passed,
+    Failed {
+        expected: CaseOutput,
+        actual: CaseOutput,
+    },
+    // The expectation was rewritten from the actual output
+    Blessed,
+    Invalid(CaseError),
+}
+
+// Every `.case` file of the directory, sorted by path
+pub fn case_paths(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) == Some(CASE_EXTENSION) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+// Run the case in the file, rewriting its expectation if it fails and `bless` is set
+pub fn run_case_file(path: &Path, bless: bool) -> io::Result<CaseResult> {
+    let mut case = match parse_case(&fs::read_to_string(path)?) {
+        Ok(case) => case,
+        Err(error) => return Ok(CaseResult::Invalid(error)),
+    };
+
+    let actual = run_case(&case.input);
+    if actual == case.expected {
+        return Ok(CaseResult::Passed);
+    }
+    if !bless {
+        return Ok(CaseResult::Failed {
+            expected: case.expected,
+            actual,
+        });
+    }
+
+    case.expected = actual;
+    fs::write(path, case.to_source())?;
+    Ok(CaseResult::Blessed)
+}
+
+pub fn run_case_directory(directory: &Path, bless: bool) -> io::Result<Vec<(PathBuf, CaseResult)>> {
+    case_paths(directory)?
+        .into_iter()
+        .map(|path| {
+            let result = run_case_file(&path, bless)?;
+            Ok((path, result))
+        })
+        .collect()
+}
@@ -0,0 +1,178 @@
+use std::fs;
+use std::path::Path;
+
+use crate::golden::{
+    parse_case, run_case, run_case_directory, run_case_file, Case, CaseError, CaseErrorKind,
+    CaseResult, SpannedError,
+};
+use crate::tokenize::{ExpressionLexingError, LexingError, NumberLexingError, OperatorKind, Token};
+
+#[test]
+fn test_checked_in_cases() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("cases");
+    let results = run_case_directory(&directory, false).unwrap();
+
+    assert!(!results.is_empty());
+    for (path, result) in results {
+        assert_eq!(
+            result,
+            CaseResult::Passed,
+            "\n case: {}\n\n",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn test_parse_tokens() {
+    let case = parse_case("# A comment\ninput \"0.5 * 12\"\nnumber 0.5\noperator *\nnumber 12\n");
+
+    assert_eq!(
+        case,
+        Ok(Case {
+            comments: vec!["A comment".to_string()],
+            input: "0.5 * 12".to_string(),
+            expected: Ok(vec![
+                Token::Number(0.5),
+                Token::Operator(OperatorKind::Multiply),
+                Token::Number(12.0),
+            ]),
+        })
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let case = parse_case("input \"1 + é\"\nerror UnexpectedCharacter 4..6\n").unwrap();
+    assert_eq!(
+        case.expected,
+        Err(SpannedError {
+            error: LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter(
+                'é'
+            )),
+            span: 4..6,
+        })
+    );
+
+    let case = parse_case("input \"1 +\"\nerror ExpectedNumber 3..3\n").unwrap();
+    assert_eq!(
+        case.expected,
+        Err(SpannedError {
+            error: LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber),
+            span: 3..3,
+        })
+    );
+}
+
+#[test]
+fn test_invalid_cases() {
+    let cases = vec![
+        ("# Nothing else\n", 0, CaseErrorKind::MissingInput),
+        ("operator +\n", 1, CaseErrorKind::ExpectationBeforeInput),
+        (
+            "input \"1\"\ninput \"2\"\n",
+            2,
+            CaseErrorKind::DuplicateInput,
+        ),
+        ("input \"1\n", 1, CaseErrorKind::InvalidString),
+        ("input 1\n", 1, CaseErrorKind::InvalidLine),
+        (
+            "input \"1\"\nnumber one\n",
+            2,
+            CaseErrorKind::InvalidNumber("one".to_string()),
+        ),
+        (
+            "input \"1\"\noperator %\n",
+            2,
+            CaseErrorKind::UnknownOperator("%".to_string()),
+        ),
+        (
+            "input \"1\"\nerror Oops 0..1\n",
+            2,
+            CaseErrorKind::UnknownError("Oops".to_string()),
+        ),
+        (
+            "input \"1 + é\"\nerror UnexpectedCharacter 4..5\n",
+            2,
+            CaseErrorKind::InvalidSpan("4..5".to_string()),
+        ),
+        (
+            "input \"1 +\"\nerror UnexpectedCharacter 3..3\n",
+            2,
+            CaseErrorKind::InvalidSpan("3..3".to_string()),
+        ),
+        (
+            "input \"1 2\"\nnumber 1\nerror ExpectedOperator 2..3\n",
+            3,
+            CaseErrorKind::TokensAndError,
+        ),
+        (
+            "input \"1 2\"\nerror ExpectedOperator 2..3\nnumber 1\n",
+            3,
+            CaseErrorKind::TokensAndError,
+        ),
+    ];
+
+    for (source, line, kind) in cases.into_iter() {
+        assert_eq!(
+            parse_case(source),
+            Err(CaseError { line, kind }),
+            "\n source: {:?}\n\n",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_run_case_spans() {
+    assert_eq!(
+        run_case("0.5 / 012"),
+        Err(SpannedError {
+            error: LexingError::IncorrectNumber(NumberLexingError::ExpectedPointAfterZero),
+            span: 7..8,
+        })
+    );
+    assert_eq!(
+        run_case(" 1 - "),
+        Err(SpannedError {
+            error: LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber),
+            span: 5..5,
+        })
+    );
+}
+
+#[test]
+fn test_source_round_trip() {
+    let sources = vec![
+        "# Escapes\ninput \"\\\"\\\\\\n\\r\\t\"\nerror UnexpectedCharacter 0..1\n",
+        "input \"12 - 0.5\"\nnumber 12\noperator -\nnumber 0.5\n",
+        "input \"\"\nerror ExpectedNumber 0..0\n",
+    ];
+
+    for source in sources.into_iter() {
+        assert_eq!(parse_case(source).unwrap().to_source(), source);
+    }
+}
+
+#[test]
+fn test_bless() {
+    let path = std::env::temp_dir().join(format!("pasta-tcs-bless-{}.case", std::process::id()));
+    fs::write(&path, "# Kept\ninput \"1 + 2\"\nnumber 3\n").unwrap();
+
+    match run_case_file(&path, false).unwrap() {
+        CaseResult::Failed { expected, actual } => {
+            assert_eq!(expected, Ok(vec![Token::Number(3.0)]));
+            assert_eq!(actual, run_case("1 + 2"));
+        }
+        result => panic!("{:?}", result),
+    }
+
+    assert_eq!(run_case_file(&path, true).unwrap(), CaseResult::Blessed);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "# Kept\ninput \"1 + 2\"\nnumber 1\noperator +\nnumber 2\n"
+    );
+    assert_eq!(run_case_file(&path, false).unwrap(), CaseResult::Passed);
+
+    fs::remove_file(&path).unwrap();
+}
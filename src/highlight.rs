@@ -0,0 +1,319 @@
+use std::ops::Range;
+
+use crate::tokenize::{ExpressionLexingError, Lexer, LexingError, State};
+
+// ====================
+// Classification
+// ====================
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HighlightKind {
+    Number,
+    Operator,
+    Paren,
+    Identifier,
+    Whitespace,
+    Error,
+}
+
+// A range of the source, in bytes
+#[derive(Clone, PartialEq, Debug)]
+pub struct HighlightSpan {
+    pub kind: HighlightKind,
+    pub range: Range<usize>,
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_number(c: char) -> bool {
+    c.is_ascii_digit() || c == '.'
+}
+
+// A lexer continuing after an operand, or expecting one
+fn lexer_after(operand: bool) -> Lexer {
+    let state = if operand {
+        State::WhitespaceBeforeOperator
+    } else {
+        State::Initial
+    };
+
+    Lexer {
+        state,
+        line_start: false,
+        ..Lexer::new()
+    }
+}
+
+struct Highlighter<'a> {
+    chars: Vec<(usize, char)>,
+    input: &'a str,
+    spans: Vec<HighlightSpan>,
+}
+
+impl Highlighter<'_> {
+    fn offset(&self, index: usize) -> usize {
+        self.chars
+            .get(index)
+            .map_or(self.input.len(), |&(offset, _)| offset)
+    }
+
+    // Add a span, merging runs of numbers, whitespace and errors
+    fn push(&mut self, kind: HighlightKind, range: Range<usize>) {
+        if let Some(last) = self.spans.last_mut() {
+            let merges = matches!(
+                kind,
+                HighlightKind::Number | HighlightKind::Whitespace | HighlightKind::Error
+            );
+            if merges && last.kind == kind && last.range.end == range.start {
+                last.range.end = range.end;
+                return;
+            }
+        }
+        self.spans.push(HighlightSpan { kind, range });
+    }
+
+    // The index after the run of characters starting at `start`
+    fn run_end(&self, start: usize, f: impl Fn(char) -> bool) -> usize {
+        (start..self.chars.len())
+            .find(|&i| !f(self.chars[i].1))
+            .unwrap_or(self.chars.len())
+    }
+
+    // Remove the span of the number being lexed before `index`, returning where it starts
+    fn take_number(&mut self, index: usize) -> usize {
+        let start = self.offset(index);
+        match self.spans.last() {
+            Some(last) if last.kind == HighlightKind::Number && last.range.end == start => {
+                self.spans.pop().unwrap().range.start
+            }
+            _ => start,
+        }
+    }
+
+    // Mark the number being lexed before `index` and the rest of its run as an error,
+    // returning the index after it
+    fn number_error(&mut self, index: usize) -> usize {
+        let start = self.take_number(index);
+
+        let end = self
+            .run_end(index, is_number)
+            .max(index + 1)
+            .min(self.chars.len());
+        self.push(HighlightKind::Error, start..self.offset(end));
+        end
+    }
+
+    // Mark the dangling operator at the end of input as an error
+    fn trailing_operator_error(&mut self) {
+        let operator = self
+            .spans
+            .iter_mut()
+            .rev()
+            .find(|span| span.kind != HighlightKind::Whitespace);
+        if let Some(span) = operator {
+            if span.kind == HighlightKind::Operator {
+                span.kind = HighlightKind::Error;
+            }
+        }
+    }
+
+    // Classify the input, restarting the lexer after every error
+    fn run(mut self) -> Vec<HighlightSpan> {
+        let mut lexer = Lexer::new();
+        let mut index = 0;
+
+        while index <= self.chars.len() {
+            let c = self.chars.get(index).map(|&(_, c)| c);
+            let before = lexer.state();
+
+            let error = match lexer.feed(c) {
+                Ok(_) => {
+                    if let Some(c) = c {
+                        let kind = match c {
+                            ' ' => HighlightKind::Whitespace,
+                            c if is_number(c) => HighlightKind::Number,
+                            _ => HighlightKind::Operator,
+                        };
+                        self.push(kind, self.offset(index)..self.offset(index + 1));
+                    }
+                    index += 1;
+                    continue;
+                }
+                Err(error) => error,
+            };
+
+            let start = self.offset(index);
+            let (next, operand) = match (error, c) {
+                (_, Some(c)) if c == '(' || c == ')' => {
+                    self.push(HighlightKind::Paren, start..self.offset(index + 1));
+                    (index + 1, c == ')')
+                }
+                (error, Some(c)) if is_identifier_start(c) => {
+                    // An unfinished number like "0." stays an error
+                    if let LexingError::IncorrectNumber(_) = error {
+                        let number_start = self.take_number(index);
+                        if number_start < start {
+                            self.push(HighlightKind::Error, number_start..start);
+                        }
+                    }
+                    let end = self.run_end(index, is_identifier);
+                    self.push(HighlightKind::Identifier, start..self.offset(end));
+                    (end, true)
+                }
+                (LexingError::IncorrectNumber(_), _) => (self.number_error(index), true),
+                (LexingError::IncorrectExpression(ExpressionLexingError::ExpectedOperator), _) => {
+                    let end = self.run_end(index, is_number);
+                    self.push(HighlightKind::Error, start..self.offset(end));
+                    (end, true)
+                }
+                (_, None) => {
+                    self.trailing_operator_error();
+                    break;
+                }
+                (_, Some(_)) => {
+                    self.push(HighlightKind::Error, start..self.offset(index + 1));
+                    (index + 1, before != State::Initial)
+                }
+            };
+
+            lexer = lexer_after(operand);
+            index = next;
+        }
+
+        self.spans
+    }
+}
+
+// Classify every character of the input, continuing after lexing errors
+pub fn highlight(input: &str) -> Vec<HighlightSpan> {
+    Highlighter {
+        chars: input.char_indices().collect(),
+        input,
+        spans: vec![],
+    }
+    .run()
+}
+
+// ====================
+// Themes
+// ====================
+
+// What to wrap every kind of span in, empty for nothing: SGR parameters like "1;31" for ANSI,
+// class names for HTML
+#[derive(Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub number: String,
+    pub operator: String,
+    pub paren: String,
+    pub identifier: String,
+    pub whitespace: String,
+    pub error: String,
+}
+
+impl Theme {
+    pub fn ansi_dark() -> Self {
+        Self {
+            number: "96".to_string(),
+            operator: "93".to_string(),
+            paren: "37".to_string(),
+            identifier: "92".to_string(),
+            whitespace: String::new(),
+            error: "1;4;91".to_string(),
+        }
+    }
+
+    pub fn ansi_light() -> Self {
+        Self {
+            number: "34".to_string(),
+            operator: "35".to_string(),
+            paren: "90".to_string(),
+            identifier: "32".to_string(),
+            whitespace: String::new(),
+            error: "1;4;31".to_string(),
+        }
+    }
+
+    // Classes named after the kinds, with a prefix
+    pub fn html_classes(prefix: &str) -> Self {
+        let class = |name: &str| format!("{}{}", prefix, name);
+
+        Self {
+            number: class("number"),
+            operator: class("operator"),
+            paren: class("paren"),
+            identifier: class("identifier"),
+            whitespace: String::new(),
+            error: class("error"),
+        }
+    }
+
+    pub fn style(&self, kind: HighlightKind) -> &str {
+        match kind {
+            HighlightKind::Number => &self.number,
+            HighlightKind::Operator => &self.operator,
+            HighlightKind::Paren => &self.paren,
+            HighlightKind::Identifier => &self.identifier,
+            HighlightKind::Whitespace => &self.whitespace,
+            HighlightKind::Error => &self.error,
+        }
+    }
+}
+
+// ====================
+// Rendering
+// ====================
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Highlight the input with ANSI escape sequences
+pub fn render_ansi(input: &str, theme: &Theme) -> String {
+    let mut output = String::with_capacity(input.len() * 2);
+
+    for span in highlight(input) {
+        let text = &input[span.range];
+        match theme.style(span.kind) {
+            "" => output.push_str(text),
+            style => output.push_str(&format!("\x1b[{}m{}\x1b[0m", style, text)),
+        }
+    }
+
+    output
+}
+
+// Highlight the input with `<span class>` markup, escaping it
+pub fn render_html(input: &str, theme: &Theme) -> String {
+    let mut output = String::with_capacity(input.len() * 4);
+
+    for span in highlight(input) {
+        let text = escape_html(&input[span.range]);
+        match theme.style(span.kind) {
+            "" => output.push_str(&text),
+            class => output.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                escape_html(class),
+                text
+            )),
+        }
+    }
+
+    output
+}
@@ -0,0 +1,154 @@
+use crate::differential::random_input;
+use crate::highlight::{highlight, render_ansi, render_html, HighlightKind, HighlightSpan, Theme};
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::tokenize;
+
+use HighlightKind::*;
+
+/// Parens, identifiers and characters to escape, on top of the shared alphabet
+const EXTRA: [char; 5] = ['(', ')', '_', '<', '&'];
+
+/// The kinds of the spans, with their text
+fn kinds(input: &str) -> Vec<(HighlightKind, &str)> {
+    highlight(input)
+        .into_iter()
+        .map(|HighlightSpan { kind, range }| (kind, &input[range]))
+        .collect()
+}
+
+#[test]
+fn test_highlight_valid_expression() {
+    assert_eq!(
+        kinds(" 0.5*12 "),
+        vec![
+            (Whitespace, " "),
+            (Number, "0.5"),
+            (Operator, "*"),
+            (Number, "12"),
+            (Whitespace, " "),
+        ]
+    );
+}
+
+#[test]
+fn test_highlight_parens_and_identifiers() {
+    assert_eq!(
+        kinds("(rate + 2) * x_1"),
+        vec![
+            (Paren, "("),
+            (Identifier, "rate"),
+            (Whitespace, " "),
+            (Operator, "+"),
+            (Whitespace, " "),
+            (Number, "2"),
+            (Paren, ")"),
+            (Whitespace, " "),
+            (Operator, "*"),
+            (Whitespace, " "),
+            (Identifier, "x_1"),
+        ]
+    );
+}
+
+#[test]
+fn test_highlight_continues_past_errors() {
+    assert_eq!(
+        kinds("12.5 + 0.7 7 * 012 / #"),
+        vec![
+            (Error, "12.5"),
+            (Whitespace, " "),
+            (Operator, "+"),
+            (Whitespace, " "),
+            (Number, "0.7"),
+            (Whitespace, " "),
+            (Error, "7"),
+            (Whitespace, " "),
+            (Operator, "*"),
+            (Whitespace, " "),
+            (Error, "012"),
+            (Whitespace, " "),
+            (Operator, "/"),
+            (Whitespace, " "),
+            (Error, "#"),
+        ]
+    );
+}
+
+#[test]
+fn test_highlight_errors_at_end_of_input() {
+    assert_eq!(
+        kinds("1 + "),
+        vec![
+            (Number, "1"),
+            (Whitespace, " "),
+            (Error, "+"),
+            (Whitespace, " ")
+        ]
+    );
+    assert_eq!(
+        kinds("1 * 0."),
+        vec![
+            (Number, "1"),
+            (Whitespace, " "),
+            (Operator, "*"),
+            (Whitespace, " "),
+            (Error, "0.")
+        ]
+    );
+    assert_eq!(
+        kinds("+ 1"),
+        vec![(Error, "+"), (Whitespace, " "), (Number, "1")]
+    );
+    assert_eq!(kinds("0.x"), vec![(Error, "0."), (Identifier, "x")]);
+    assert_eq!(kinds(""), vec![]);
+}
+
+#[test]
+fn test_render_ansi() {
+    assert_eq!(
+        render_ansi("1 +x", &Theme::ansi_dark()),
+        "\x1b[96m1\x1b[0m \x1b[93m+\x1b[0m\x1b[92mx\x1b[0m"
+    );
+}
+
+#[test]
+fn test_render_html() {
+    let theme = Theme {
+        whitespace: "space".to_string(),
+        ..Theme::html_classes("pasta-")
+    };
+
+    assert_eq!(
+        render_html("1 <", &theme),
+        "<span class=\"pasta-number\">1</span><span class=\"space\"> </span>\
+         <span class=\"pasta-error\">&lt;</span>"
+    );
+}
+
+#[test]
+fn test_highlight_random() {
+    let alphabet: Vec<char> = ALPHABET.iter().chain(EXTRA.iter()).copied().collect();
+
+    seeded::check(
+        5000,
+        || random_input(&alphabet, 30),
+        |input| {
+            let spans = highlight(input);
+
+            // The spans cover the whole input, in order
+            let mut end = 0;
+            for span in spans.iter() {
+                assert_eq!(span.range.start, end);
+                assert!(span.range.start < span.range.end);
+                end = span.range.end;
+            }
+            assert_eq!(end, input.len());
+
+            // Valid inputs have no errors
+            if tokenize(input).is_ok() {
+                assert!(spans.iter().all(|span| span.kind != Error));
+            }
+        },
+    );
+}
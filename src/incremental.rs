@@ -0,0 +1,284 @@
+use std::ops::Range;
+
+use crate::tokenize::{Lexer, LexingError, State, Token};
+
+// Incremental re-lexing of files of expressions, one per line as in line-oriented mode.
+//
+// The lexer is in `State::Initial` with an empty buffer at the start of every line and right
+// after every operator, so lexing can restart from there with nothing but the text after it.
+// After an edit, the text is lexed again from the last such boundary before the edit, until a
+// boundary after the edit where the old stream was in the same state. From there on the text
+// is unchanged, so the old tokens are kept, shifted by the length difference.
+
+// ====================
+// Spanned tokens
+// ====================
+
+// The output of the lexer for a range of the text, in bytes. Numbers cover their digits,
+// operators and ends of expression their character, and errors the character they were
+// raised at. Spans at the end of input are empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Result<Token, LexingError>,
+    pub span: Range<usize>,
+}
+
+impl SpannedToken {
+    fn is_operator(&self) -> bool {
+        matches!(self.token, Ok(Token::Operator(_)))
+    }
+
+    // Whether the token was emitted for a character before `offset`, rather than for the
+    // character at it or after
+    fn is_emitted_before(&self, offset: usize) -> bool {
+        self.span.end <= offset && self.span.start < offset
+    }
+}
+
+// A lexer keeping track of the spans of its tokens
+struct SpanLexer {
+    lexer: Lexer,
+    // Where the number being lexed started
+    number_start: usize,
+}
+
+impl SpanLexer {
+    // A line-oriented lexer resuming at a line start, or right after an operator
+    fn new(line_start: bool) -> Self {
+        Self::with_lexer(Lexer {
+            line_start,
+            ..Lexer::line_oriented()
+        })
+    }
+
+    fn with_lexer(lexer: Lexer) -> Self {
+        Self {
+            lexer,
+            number_start: 0,
+        }
+    }
+
+    // Feed the character at the offset, `None` at the end of input
+    fn feed(&mut self, offset: usize, c: Option<char>, tokens: &mut Vec<SpannedToken>) {
+        let before = self.lexer.state();
+        let end = offset + c.map_or(0, char::len_utf8);
+
+        match self.lexer.feed(c) {
+            Ok(emitted) => tokens.extend(emitted.into_iter().map(|token| {
+                let span = match token {
+                    Token::Number(_) | Token::Decimal(_) | Token::Integer(_) => {
+                        self.number_start..offset
+                    }
+                    Token::Operator(_) | Token::EndOfExpression => offset..end,
+                };
+                SpannedToken {
+                    token: Ok(token),
+                    span,
+                }
+            })),
+            Err(error) => tokens.push(SpannedToken {
+                token: Err(error),
+                span: offset..end,
+            }),
+        }
+
+        let in_number = matches!(self.lexer.state(), State::NumberZeroInteger | State::Number);
+        if before == State::Initial && in_number {
+            self.number_start = offset;
+        }
+    }
+}
+
+// Lex the text from scratch, one expression per line
+pub fn tokenize_spanned(text: &str) -> Vec<SpannedToken> {
+    tokenize_spanned_with(Lexer::line_oriented(), text)
+}
+
+// Lex the text from scratch with the lexer, reset first. This is where the spans of tokens and
+// errors are worked out for the golden cases and the language server too.
+pub fn tokenize_spanned_with(mut lexer: Lexer, text: &str) -> Vec<SpannedToken> {
+    lexer.reset();
+    let mut tokens = vec![];
+    let mut lexer = SpanLexer::with_lexer(lexer);
+
+    for (offset, c) in text.char_indices() {
+        lexer.feed(offset, Some(c), &mut tokens);
+    }
+    lexer.feed(text.len(), None, &mut tokens);
+
+    tokens
+}
+
+// ====================
+// Edits
+// ====================
+
+// Replace a range of the text, in bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn apply(&self, text: &str) -> String {
+        let mut edited = text.to_string();
+        edited.replace_range(self.range.clone(), &self.replacement);
+        edited
+    }
+
+    // Where the replacement ends in the edited text
+    fn new_end(&self) -> usize {
+        self.range.start + self.replacement.len()
+    }
+}
+
+// The tokens replaced by an edit: `old` indexes the stream before it, `new` the stream after
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenChange {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+// A text and its tokens, kept up to date through edits
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenStream {
+    text: String,
+    tokens: Vec<SpannedToken>,
+}
+
+impl TokenStream {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            tokens: tokenize_spanned(text),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn tokens(&self) -> &[SpannedToken] {
+        &self.tokens
+    }
+
+    // The last offset before `offset` the lexer can restart from, with whether it is a line
+    // start
+    fn restart_point(&self, offset: usize) -> (usize, bool) {
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let emitted = self
+            .tokens
+            .partition_point(|token| token.is_emitted_before(offset));
+
+        let operator_end = self.tokens[..emitted]
+            .iter()
+            .rev()
+            .take_while(|token| token.span.start >= line_start)
+            .find(|token| token.is_operator())
+            .map(|token| token.span.end);
+
+        match operator_end {
+            Some(end) => (end, false),
+            None => (line_start, true),
+        }
+    }
+
+    // Whether the old stream has an operator ending at the offset
+    fn has_operator_ending_at(&self, offset: usize) -> bool {
+        let emitted = self
+            .tokens
+            .partition_point(|token| token.is_emitted_before(offset));
+        emitted > 0
+            && self.tokens[emitted - 1].is_operator()
+            && self.tokens[emitted - 1].span.end == offset
+    }
+
+    // Apply the edit, lexing again only around it, and return the tokens that changed
+    pub fn edit(&mut self, edit: &TextEdit) -> TokenChange {
+        let text = edit.apply(&self.text);
+        let new_end = edit.new_end();
+        // Offsets after the edit move by the difference in length
+        let to_old = |offset: usize| offset - new_end + edit.range.end;
+        let to_new = |offset: usize| offset - edit.range.end + new_end;
+
+        let (restart, line_start) = self.restart_point(edit.range.start);
+        let old_start = self
+            .tokens
+            .partition_point(|token| token.is_emitted_before(restart));
+
+        // Lex until both streams are at the same boundary after the edit, or to the end
+        let mut relexed = vec![];
+        let mut lexer = SpanLexer::new(line_start);
+        let mut realigned = None;
+        for (offset, c) in text[restart..].char_indices() {
+            let offset = restart + offset;
+            let emitted = relexed.len();
+            lexer.feed(offset, Some(c), &mut relexed);
+
+            let end = offset + c.len_utf8();
+            if end <= new_end {
+                continue;
+            }
+            let after_operator = relexed[emitted..].last().is_some_and(|token| {
+                token.is_operator() && self.has_operator_ending_at(to_old(end))
+            });
+            if c == '\n' || after_operator {
+                realigned = Some(end);
+                break;
+            }
+        }
+
+        let old_end = match realigned {
+            Some(end) => self
+                .tokens
+                .partition_point(|token| token.is_emitted_before(to_old(end))),
+            None => {
+                lexer.feed(text.len(), None, &mut relexed);
+                self.tokens.len()
+            }
+        };
+
+        // Shift the kept tokens after the edit
+        let mut suffix: Vec<SpannedToken> = self.tokens[old_end..]
+            .iter()
+            .map(|token| SpannedToken {
+                token: token.token.clone(),
+                span: to_new(token.span.start)..to_new(token.span.end),
+            })
+            .collect();
+
+        // Leave the tokens lexed again identically out of the change
+        let unchanged_prefix = relexed
+            .iter()
+            .zip(self.tokens[old_start..old_end].iter())
+            .take_while(|(new, old)| new == old)
+            .count();
+        let unchanged_suffix = relexed[unchanged_prefix..]
+            .iter()
+            .rev()
+            .zip(
+                self.tokens[old_start + unchanged_prefix..old_end]
+                    .iter()
+                    .rev(),
+            )
+            .take_while(|(new, old)| {
+                new.token == old.token
+                    && old.span.start >= edit.range.end
+                    && new.span == (to_new(old.span.start)..to_new(old.span.end))
+            })
+            .count();
+
+        let change = TokenChange {
+            old: old_start + unchanged_prefix..old_end - unchanged_suffix,
+            new: old_start + unchanged_prefix..old_start + relexed.len() - unchanged_suffix,
+        };
+
+        self.tokens.truncate(old_start);
+        self.tokens.append(&mut relexed);
+        self.tokens.append(&mut suffix);
+        self.text = text;
+
+        change
+    }
+}
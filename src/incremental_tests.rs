@@ -0,0 +1,253 @@
+use rand::Rng;
+
+use crate::differential::random_input;
+use crate::incremental::{tokenize_spanned, SpannedToken, TextEdit, TokenChange, TokenStream};
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{
+    ExpressionLexingError, Lexer, LexingError, NumberLexingError, OperatorKind, Token,
+};
+
+fn token(token: Token, span: std::ops::Range<usize>) -> SpannedToken {
+    SpannedToken {
+        token: Ok(token),
+        span,
+    }
+}
+
+fn error(error: LexingError, span: std::ops::Range<usize>) -> SpannedToken {
+    SpannedToken {
+        token: Err(error),
+        span,
+    }
+}
+
+fn edit(start: usize, end: usize, replacement: &str) -> TextEdit {
+    TextEdit {
+        range: start..end,
+        replacement: replacement.to_string(),
+    }
+}
+
+/// Apply the edit incrementally, checking the result against lexing from scratch
+fn assert_edit(text: &str, edit: &TextEdit) -> TokenChange {
+    let mut stream = TokenStream::new(text);
+    let old = stream.tokens().to_vec();
+    let change = stream.edit(edit);
+    let expected = TokenStream::new(&edit.apply(text));
+
+    assert_eq!(
+        stream, expected,
+        "\n text: {:?}\n edit: {:?}\n\n",
+        text, edit
+    );
+
+    // Only the tokens of the change differ
+    let new = stream.tokens();
+    assert_eq!(old[..change.old.start], new[..change.new.start]);
+    assert_eq!(old.len() - change.old.end, new.len() - change.new.end);
+    for (old, new) in old[change.old.end..]
+        .iter()
+        .zip(new[change.new.end..].iter())
+    {
+        assert_eq!(old.token, new.token);
+    }
+
+    change
+}
+
+#[test]
+fn test_tokenize_spanned() {
+    use OperatorKind::*;
+
+    assert_eq!(
+        tokenize_spanned("12 + 0.5\n1.2 +\n1 -"),
+        vec![
+            token(Token::Number(12.0), 0..2),
+            token(Token::Operator(Add), 3..4),
+            token(Token::Number(0.5), 5..8),
+            token(Token::EndOfExpression, 8..9),
+            error(
+                LexingError::IncorrectNumber(NumberLexingError::NonZeroIntegerBeforePoint),
+                10..11
+            ),
+            token(Token::Number(1.0), 15..16),
+            token(Token::Operator(Subtract), 17..18),
+            error(
+                LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber),
+                18..18
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_spanned_matches_lexer() {
+    seeded::check(
+        2000,
+        || random_input(&ALPHABET, 30),
+        |input| {
+            let mut lexer = Lexer::line_oriented();
+            let mut expected = vec![];
+            for c in input.chars().map(Some).chain(std::iter::once(None)) {
+                match lexer.feed(c) {
+                    Ok(tokens) => expected.extend(tokens.into_iter().map(Ok)),
+                    Err(error) => expected.push(Err(error)),
+                }
+            }
+
+            let spanned = tokenize_spanned(input);
+            let tokens: Vec<_> = spanned.iter().map(|token| token.token.clone()).collect();
+            assert_eq!(tokens, expected);
+
+            // Numbers and operators span their text
+            for token in spanned.iter() {
+                let text = &input[token.span.clone()];
+                match &token.token {
+                    Ok(Token::Number(number)) => assert_eq!(text.parse::<f64>(), Ok(*number)),
+                    Ok(Token::Operator(_)) => assert_eq!(text.len(), 1),
+                    _ => {}
+                }
+            }
+        },
+    );
+}
+
+#[test]
+fn test_edit_within_line() {
+    // Replacing "3" re-lexes from the "+" before it up to the "+" after it
+    let change = assert_edit("1 + 2 + 3 + 4 + 5", &edit(8, 9, "33"));
+    assert_eq!(
+        change,
+        TokenChange {
+            old: 4..5,
+            new: 4..5
+        }
+    );
+
+    // Inserting an operator and a number adds two tokens
+    let change = assert_edit("1 + 2", &edit(1, 1, " * 7"));
+    assert_eq!(
+        change,
+        TokenChange {
+            old: 1..1,
+            new: 1..3
+        }
+    );
+
+    // Edits keeping the tokens change nothing
+    let change = assert_edit("1 + 2", &edit(1, 1, "   "));
+    assert_eq!(
+        change,
+        TokenChange {
+            old: 1..1,
+            new: 1..1
+        }
+    );
+}
+
+#[test]
+fn test_edit_only_changes_its_line() {
+    let text = "1 + 2\n3 * 4\n5 - 6\n";
+
+    let change = assert_edit(text, &edit(8, 9, "4."));
+    assert_eq!(
+        change,
+        TokenChange {
+            old: 5..8,
+            new: 5..6
+        }
+    );
+
+    // Joining two lines
+    let change = assert_edit(text, &edit(5, 6, " + "));
+    assert_eq!(
+        change,
+        TokenChange {
+            old: 3..4,
+            new: 3..4
+        }
+    );
+
+    // At the end of input
+    let change = assert_edit("1 +", &edit(3, 3, " 2"));
+    assert_eq!(
+        change,
+        TokenChange {
+            old: 2..3,
+            new: 2..4
+        }
+    );
+}
+
+/// The offset, moved back to the start of the character it falls in
+fn boundary(text: &str, offset: usize) -> usize {
+    (0..=offset.min(text.len()))
+        .rev()
+        .find(|&offset| text.is_char_boundary(offset))
+        .unwrap()
+}
+
+#[test]
+fn test_edit_random() {
+    seeded::check(
+        3000,
+        || {
+            let text = random_input(&ALPHABET, 30);
+            let replacement = random_input(&ALPHABET, 6);
+
+            let mut rng = seeded::thread_rng();
+            let start = rng.gen_range(0..=text.len());
+            let end = rng.gen_range(start..=text.len());
+            (text, replacement, start, end)
+        },
+        |(text, replacement, start, end)| {
+            // Shrinking can leave the range anywhere
+            let start = boundary(text, *start);
+            let end = boundary(text, (*end).max(start));
+            let edit = TextEdit {
+                range: start..end,
+                replacement: replacement.clone(),
+            };
+
+            assert_edit(text, &edit);
+        },
+    );
+}
+
+/// Edits as a replacement, a start and a length, of a text which starts empty
+fn random_edits() -> Vec<(String, usize, usize)> {
+    let mut rng = seeded::thread_rng();
+    let mut len = 0;
+
+    (0..50)
+        .map(|_| {
+            let replacement = random_input(&ALPHABET, 4);
+            let start = rng.gen_range(0..=len);
+            let length = rng.gen_range(0..=(len - start).min(3));
+            len = len - length + replacement.len();
+            (replacement, start, length)
+        })
+        .collect()
+}
+
+#[test]
+fn test_successive_edits() {
+    seeded::check(100, random_edits, |edits| {
+        let mut stream = TokenStream::new("");
+        let mut text = String::new();
+
+        for (replacement, start, length) in edits.iter() {
+            let start = boundary(&text, *start);
+            let end = boundary(&text, start + length);
+            let edit = TextEdit {
+                range: start..end,
+                replacement: replacement.clone(),
+            };
+
+            stream.edit(&edit);
+            text = edit.apply(&text);
+            assert_eq!(stream, TokenStream::new(&text), "\n edit: {:?}\n\n", edit);
+        }
+    });
+}
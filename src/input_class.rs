@@ -0,0 +1,174 @@
+use crate::tokenize::{Lexer, State};
+use crate::transitions::{transition, Emit};
+
+// ====================
+// Input classes
+// ====================
+
+// Every input the lexer can be fed falls in exactly one class
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum InputClass {
+    ZeroDigit,
+    NonZeroDigit,
+    Point,
+    Whitespace,
+    Operator,
+    Other,
+    // Like any other character, except in line-oriented mode where it ends the expression
+    Newline,
+    EndOfInput,
+}
+
+impl InputClass {
+    pub const ALL: [InputClass; 8] = [
+        InputClass::ZeroDigit,
+        InputClass::NonZeroDigit,
+        InputClass::Point,
+        InputClass::Whitespace,
+        InputClass::Operator,
+        InputClass::Other,
+        InputClass::Newline,
+        InputClass::EndOfInput,
+    ];
+
+    // Get the class of a character `Some(char)`, or of `None` for end of string
+    pub fn of(c: Option<char>) -> Self {
+        match c {
+            None => InputClass::EndOfInput,
+            Some('0') => InputClass::ZeroDigit,
+            Some('1'..='9') => InputClass::NonZeroDigit,
+            Some('.') => InputClass::Point,
+            Some(' ') => InputClass::Whitespace,
+            Some('-') | Some('+') | Some('/') | Some('*') => InputClass::Operator,
+            Some('\n') => InputClass::Newline,
+            Some(_) => InputClass::Other,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+// ====================
+// Set of input classes
+// ====================
+
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub struct InputClassSet(u8);
+
+impl InputClassSet {
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn of(classes: &[InputClass]) -> Self {
+        classes
+            .iter()
+            .fold(Self::empty(), |set, &class| set.with(class))
+    }
+
+    pub fn with(self, class: InputClass) -> Self {
+        Self(self.0 | class.bit())
+    }
+
+    pub fn contains(&self, class: InputClass) -> bool {
+        self.0 & class.bit() != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = InputClass> + '_ {
+        InputClass::ALL
+            .iter()
+            .copied()
+            .filter(move |&class| self.contains(class))
+    }
+
+    // Describe the set in English, e.g. "a digit, whitespace or an operator"
+    pub fn describe(&self) -> String {
+        let mut names = vec![];
+
+        let zero = self.contains(InputClass::ZeroDigit);
+        let non_zero = self.contains(InputClass::NonZeroDigit);
+        match (zero, non_zero) {
+            (true, true) => names.push("a digit"),
+            (true, false) => names.push("a zero"),
+            (false, true) => names.push("a non-zero digit"),
+            (false, false) => {}
+        }
+
+        for class in self.iter() {
+            match class {
+                InputClass::Point => names.push("a decimal point"),
+                InputClass::Whitespace => names.push("whitespace"),
+                InputClass::Operator => names.push("an operator"),
+                InputClass::Other => names.push("any other character"),
+                InputClass::Newline => names.push("a newline"),
+                InputClass::EndOfInput => names.push("the end of input"),
+                InputClass::ZeroDigit | InputClass::NonZeroDigit => {}
+            }
+        }
+
+        match names.split_last() {
+            None => "nothing".to_string(),
+            Some((last, [])) => last.to_string(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        }
+    }
+}
+
+impl std::fmt::Debug for InputClassSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+// ====================
+// Expected inputs
+// ====================
+
+impl State {
+    // The input classes that can be fed in this state without an error, as the transition table
+    // says. Nothing is expected once the lexing ended or failed.
+    pub fn expected_inputs(self) -> InputClassSet {
+        if self == State::End || self == State::Error {
+            return InputClassSet::empty();
+        }
+
+        InputClass::ALL
+            .iter()
+            .copied()
+            .filter(|&input| !matches!(transition(self, input).emit, Emit::Error(_)))
+            .fold(InputClassSet::empty(), |set, input| set.with(input))
+    }
+}
+
+impl Lexer {
+    // The input classes that can be fed next without an error
+    pub fn expected_inputs(&self) -> InputClassSet {
+        if !self.line_oriented {
+            return self.state.expected_inputs();
+        }
+
+        match self.state {
+            State::End => InputClassSet::empty(),
+            // The rest of a failed line is skipped, until the newline starting the next one
+            State::Error => InputClassSet::of(&[InputClass::Newline, InputClass::EndOfInput]),
+            state => {
+                let mut expected = state.expected_inputs();
+                // A newline ends the expression like the end of input does
+                if expected.contains(InputClass::EndOfInput) {
+                    expected = expected.with(InputClass::Newline);
+                }
+                // At the start of a line the input can end, without an empty expression
+                if self.line_start {
+                    expected = expected.with(InputClass::EndOfInput);
+                }
+                expected
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::input_class::{InputClass, InputClassSet};
+use crate::test_helpers::{lexer_in_state, representative};
+use crate::tokenize::{Lexer, State};
+
+#[test]
+fn test_expected_inputs_agree_with_feed() {
+    for &state in State::ALL.iter() {
+        let expected = lexer_in_state(state).expected_inputs();
+
+        for &class in InputClass::ALL.iter() {
+            let mut lexer = lexer_in_state(state);
+            let result = lexer.feed(representative(class));
+            let accepted = result.is_ok() && lexer.state() != State::Error;
+
+            // Nothing can follow the end of the lexing, even though feed ignores it
+            let accepted = accepted && state != State::End && state != State::Error;
+
+            assert_eq!(
+                expected.contains(class),
+                accepted,
+                "\n state: {:?}, class: {:?}\n\n",
+                state,
+                class
+            );
+        }
+    }
+}
+
+#[test]
+fn test_expected_inputs_agree_with_line_oriented_feed() {
+    // Only the initial state can be at the start of a line
+    let states = State::ALL
+        .iter()
+        .map(|&state| (state, false))
+        .chain(std::iter::once((State::Initial, true)));
+
+    for (state, line_start) in states {
+        let line_oriented_lexer = || {
+            let mut snapshot = lexer_in_state(state).snapshot();
+            snapshot.line_oriented = true;
+            snapshot.line_start = line_start;
+            Lexer::from_snapshot(&snapshot).unwrap()
+        };
+        let expected = line_oriented_lexer().expected_inputs();
+
+        for &class in InputClass::ALL.iter() {
+            let mut lexer = line_oriented_lexer();
+            let result = lexer.feed(representative(class));
+            let accepted = result.is_ok() && lexer.state() != State::Error && state != State::End;
+
+            assert_eq!(
+                expected.contains(class),
+                accepted,
+                "\n state: {:?}, line start: {}, class: {:?}\n\n",
+                state,
+                line_start,
+                class
+            );
+        }
+    }
+}
+
+#[test]
+fn test_expected_newline() {
+    let mut lexer = Lexer::line_oriented();
+    assert_eq!(
+        lexer.expected_inputs().describe(),
+        "a digit, whitespace or the end of input"
+    );
+
+    lexer.feed(Some('1')).unwrap();
+    assert_eq!(
+        lexer.expected_inputs().describe(),
+        "a digit, whitespace, an operator, a newline or the end of input"
+    );
+
+    // Only a newline starts lexing again after an error
+    lexer.feed(Some('x')).unwrap_err();
+    assert_eq!(
+        lexer.expected_inputs(),
+        InputClassSet::of(&[InputClass::Newline, InputClass::EndOfInput])
+    );
+    lexer.feed(Some('\n')).unwrap();
+    assert_eq!(
+        lexer.expected_inputs(),
+        Lexer::line_oriented().expected_inputs()
+    );
+
+    // Newlines are other characters when lexing a single expression
+    assert_eq!(InputClass::of(Some('\n')), InputClass::Newline);
+    assert!(!State::Number
+        .expected_inputs()
+        .contains(InputClass::Newline));
+}
+
+#[test]
+fn test_classify() {
+    let cases = vec![
+        (Some('0'), InputClass::ZeroDigit),
+        (Some('5'), InputClass::NonZeroDigit),
+        (Some('9'), InputClass::NonZeroDigit),
+        (Some('.'), InputClass::Point),
+        (Some(' '), InputClass::Whitespace),
+        (Some('-'), InputClass::Operator),
+        (Some('+'), InputClass::Operator),
+        (Some('/'), InputClass::Operator),
+        (Some('*'), InputClass::Operator),
+        (Some('\t'), InputClass::Other),
+        (Some('\n'), InputClass::Newline),
+        (Some('٣'), InputClass::Other),
+        (None, InputClass::EndOfInput),
+    ];
+
+    for (c, class) in cases.into_iter() {
+        assert_eq!(InputClass::of(c), class, "\n input: {:?}\n\n", c);
+    }
+}
+
+#[test]
+fn test_expected_after_point() {
+    let mut lexer = Lexer::new();
+    for c in "3 + 0.".chars() {
+        lexer.feed(Some(c)).unwrap();
+    }
+
+    let expected = lexer.expected_inputs();
+    assert_eq!(
+        expected,
+        InputClassSet::of(&[InputClass::ZeroDigit, InputClass::NonZeroDigit])
+    );
+    assert_eq!(expected.describe(), "a digit");
+}
+
+#[test]
+fn test_describe() {
+    let cases = vec![
+        (State::Initial, "a digit or whitespace"),
+        (
+            State::NumberZeroInteger,
+            "a decimal point, whitespace, an operator or the end of input",
+        ),
+        (
+            State::Number,
+            "a digit, whitespace, an operator or the end of input",
+        ),
+        (
+            State::WhitespaceBeforeOperator,
+            "whitespace, an operator or the end of input",
+        ),
+        (State::End, "nothing"),
+    ];
+
+    for (state, description) in cases.into_iter() {
+        assert_eq!(state.expected_inputs().describe(), description);
+    }
+    assert_eq!(
+        InputClassSet::of(&[InputClass::NonZeroDigit]).describe(),
+        "a non-zero digit"
+    );
+}
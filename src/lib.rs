@@ -0,0 +1,86 @@
+pub mod decimal;
+pub mod dfa;
+pub mod evaluate;
+pub mod format;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+pub mod golden;
+pub mod highlight;
+pub mod incremental;
+pub mod input_class;
+pub mod lossless;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+pub mod parallel;
+pub mod reader;
+pub mod scan;
+pub mod shrink;
+pub mod snapshot;
+pub mod spec;
+pub mod spec_lexer;
+#[cfg(feature = "async")]
+pub mod stream;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod token_source;
+pub mod tokenize;
+pub mod transitions;
+
+#[cfg(test)]
+mod conformance_tests;
+#[cfg(test)]
+mod decimal_tests;
+#[cfg(test)]
+mod dfa_tests;
+#[cfg(test)]
+mod differential;
+#[cfg(test)]
+mod differential_tests;
+#[cfg(test)]
+mod evaluate_tests;
+#[cfg(test)]
+mod format_tests;
+#[cfg(test)]
+mod fuzz_tests;
+#[cfg(test)]
+mod golden_tests;
+#[cfg(test)]
+mod highlight_tests;
+#[cfg(test)]
+mod incremental_tests;
+#[cfg(test)]
+mod input_class_tests;
+#[cfg(test)]
+mod line_mode_tests;
+#[cfg(test)]
+mod lossless_tests;
+#[cfg(all(test, feature = "lsp"))]
+mod lsp_tests;
+#[cfg(test)]
+mod parallel_tests;
+#[cfg(test)]
+mod ported_suites;
+#[cfg(test)]
+mod reader_tests;
+#[cfg(test)]
+mod reference_lexer;
+#[cfg(test)]
+mod scan_tests;
+#[cfg(test)]
+mod seeded;
+#[cfg(test)]
+mod seeded_tests;
+#[cfg(test)]
+mod shrink_tests;
+#[cfg(test)]
+mod snapshot_tests;
+#[cfg(test)]
+mod spec_tests;
+#[cfg(all(test, feature = "async"))]
+mod stream_tests;
+#[cfg(test)]
+mod test_helpers;
+#[cfg(test)]
+mod testing_tests;
+#[cfg(test)]
+mod transitions_tests;
@@ -0,0 +1,168 @@
+use crate::tokenize::{
+    tokenize, tokenize_with, ExpressionLexingError, Lexer, LexingError, NumberLexingError,
+    OperatorKind, State, Token,
+};
+
+/// Feed the string followed by EOI, collecting the result of every `feed` call that returned something
+fn feed_all(lexer: &mut Lexer, string: &str) -> Vec<Result<Vec<Token>, LexingError>> {
+    string
+        .chars()
+        .map(Some)
+        .chain(std::iter::once(None))
+        .map(|c| lexer.feed(c))
+        .filter(|result| result != &Ok(vec![]))
+        .collect()
+}
+
+#[test]
+fn test_line_mode_multiple_expressions() {
+    let mut lexer = Lexer::line_oriented();
+    let tokens: Vec<Token> = feed_all(&mut lexer, "1 + 2\n0.5*3 \n7")
+        .into_iter()
+        .flat_map(|result| result.unwrap())
+        .collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Number(1.0),
+            Token::Operator(OperatorKind::Add),
+            Token::Number(2.0),
+            Token::EndOfExpression,
+            Token::Number(0.5),
+            Token::Operator(OperatorKind::Multiply),
+            Token::Number(3.0),
+            Token::EndOfExpression,
+            Token::Number(7.0),
+            Token::EndOfExpression,
+        ]
+    );
+    assert!(lexer.is_ended());
+}
+
+#[test]
+fn test_line_mode_trailing_newline() {
+    let mut lexer = Lexer::line_oriented();
+
+    assert_eq!(
+        feed_all(&mut lexer, "42\n"),
+        vec![Ok(vec![Token::Number(42.0), Token::EndOfExpression])]
+    );
+    assert!(lexer.is_ended());
+}
+
+#[test]
+fn test_line_mode_recovers_after_error() {
+    let mut lexer = Lexer::line_oriented();
+
+    // The rest of the line after an error is skipped, and the line has no end of expression token
+    assert_eq!(
+        feed_all(&mut lexer, "1 2 + 3\n0.\n+\n4"),
+        vec![
+            Ok(vec![Token::Number(1.0)]),
+            Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::ExpectedOperator
+            )),
+            Err(LexingError::IncorrectNumber(
+                NumberLexingError::ExpectedDigitAfterPoint
+            )),
+            Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::ExpectedNumber
+            )),
+            Ok(vec![Token::Number(4.0), Token::EndOfExpression]),
+        ]
+    );
+}
+
+#[test]
+fn test_line_mode_empty_line() {
+    let mut lexer = Lexer::line_oriented();
+
+    assert_eq!(
+        feed_all(&mut lexer, "1\n\n2"),
+        vec![
+            Ok(vec![Token::Number(1.0), Token::EndOfExpression]),
+            Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::ExpectedNumber
+            )),
+            Ok(vec![Token::Number(2.0), Token::EndOfExpression]),
+        ]
+    );
+}
+
+#[test]
+fn test_newline_without_line_mode() {
+    assert_eq!(
+        tokenize("1\n2"),
+        Err(LexingError::IncorrectExpression(
+            ExpressionLexingError::UnexpectedCharacter('\n')
+        ))
+    );
+}
+
+#[test]
+fn test_reset_after_end_and_error() {
+    let mut lexer = Lexer::new();
+
+    assert_eq!(tokenize_with(&mut lexer, "3 - 0.25"), tokenize("3 - 0.25"));
+    assert_eq!(lexer.state(), State::End);
+
+    lexer.reset();
+    assert_eq!(
+        tokenize_with(&mut lexer, "3 -"),
+        Err(LexingError::IncorrectExpression(
+            ExpressionLexingError::ExpectedNumber
+        ))
+    );
+    assert_eq!(lexer.state(), State::Error);
+
+    lexer.reset();
+    assert_eq!(lexer.state(), State::Initial);
+    assert_eq!(tokenize_with(&mut lexer, "0.5"), tokenize("0.5"));
+}
+
+#[test]
+fn test_tokenize_with_reuses_lexer() {
+    let mut lexer = Lexer::new();
+
+    assert_eq!(
+        tokenize_with(&mut lexer, "+"),
+        Err(LexingError::IncorrectExpression(
+            ExpressionLexingError::ExpectedNumber
+        ))
+    );
+    assert_eq!(tokenize_with(&mut lexer, "1"), tokenize("1"));
+    assert_eq!(lexer.state(), State::End);
+
+    // An ended lexer doesn't hide the errors of the next input
+    assert_eq!(tokenize_with(&mut lexer, "1 2"), tokenize("1 2"));
+    assert_eq!(tokenize_with(&mut lexer, "0.5 * 4"), tokenize("0.5 * 4"));
+
+    let mut lexer = Lexer::line_oriented();
+    assert_eq!(
+        tokenize_with(&mut lexer, "0.\n"),
+        tokenize_with(&mut Lexer::line_oriented(), "0.\n")
+    );
+    assert_eq!(
+        tokenize_with(&mut lexer, "1\n2"),
+        tokenize_with(&mut Lexer::line_oriented(), "1\n2")
+    );
+}
+
+#[test]
+fn test_reset_keeps_buffer_capacity() {
+    let mut lexer = Lexer::new();
+    let mut partial = "9".repeat(100);
+    partial.push_str(" +");
+    for c in partial.chars() {
+        lexer.feed(Some(c)).unwrap();
+    }
+    // Stop in the middle of a number so the buffer is not empty
+    lexer.feed(Some('1')).unwrap();
+    let capacity = lexer.buffer.capacity();
+
+    lexer.reset();
+
+    assert!(lexer.buffer.is_empty());
+    assert_eq!(lexer.buffer.capacity(), capacity);
+}
@@ -0,0 +1,124 @@
+use crate::tokenize::{Lexer, LexingError, OperatorKind, Token};
+
+// A token keeping the exact text it was lexed from, whitespace included
+#[derive(Debug, Clone, PartialEq)]
+pub enum LosslessToken {
+    // The lexeme is kept as written, e.g. "0.50" rather than 0.5
+    Number { value: f64, text: String },
+    Operator { kind: OperatorKind, text: String },
+    Whitespace(String),
+}
+
+impl LosslessToken {
+    pub fn text(&self) -> &str {
+        match self {
+            LosslessToken::Number { text, .. } => text,
+            LosslessToken::Operator { text, .. } => text,
+            LosslessToken::Whitespace(text) => text,
+        }
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, LosslessToken::Whitespace(_))
+    }
+
+    // The token `tokenize` returns for it, if any
+    pub fn to_token(&self) -> Option<Token> {
+        match self {
+            LosslessToken::Number { value, .. } => Some(Token::Number(*value)),
+            LosslessToken::Operator { kind, .. } => Some(Token::Operator(*kind)),
+            LosslessToken::Whitespace(_) => None,
+        }
+    }
+}
+
+// ====================
+// The lexer
+// ====================
+
+// A lexer emitting whitespace and the text of every token, on top of `Lexer`
+#[derive(Clone, Default)]
+pub struct LosslessLexer {
+    lexer: Lexer,
+    // The characters of the number being lexed
+    number: String,
+    // The whitespace since the last token
+    whitespace: String,
+}
+
+impl LosslessLexer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.lexer.is_ended()
+    }
+
+    fn flush_whitespace(&mut self, tokens: &mut Vec<LosslessToken>) {
+        if !self.whitespace.is_empty() {
+            tokens.push(LosslessToken::Whitespace(std::mem::take(
+                &mut self.whitespace,
+            )));
+        }
+    }
+
+    // Feed a character `Some(char)` to the lexer, or feed `None` for end of string.
+    pub fn feed(&mut self, c: Option<char>) -> Result<Vec<LosslessToken>, LexingError> {
+        let emitted = self.lexer.feed(c)?;
+        let mut tokens = vec![];
+
+        // Whitespace always ends a number, so both are never pending at once
+        for token in emitted.iter() {
+            if let Token::Number(value) = token {
+                tokens.push(LosslessToken::Number {
+                    value: *value,
+                    text: std::mem::take(&mut self.number),
+                });
+            }
+        }
+
+        match c {
+            Some(' ') => self.whitespace.push(' '),
+            Some(c) => {
+                self.flush_whitespace(&mut tokens);
+                match emitted.last() {
+                    Some(Token::Operator(kind)) => tokens.push(LosslessToken::Operator {
+                        kind: *kind,
+                        text: c.to_string(),
+                    }),
+                    _ => self.number.push(c),
+                }
+            }
+            None => self.flush_whitespace(&mut tokens),
+        }
+
+        Ok(tokens)
+    }
+}
+
+// Get the lossless token list for string, whose texts concatenate back to it
+pub fn tokenize_lossless(string: &str) -> Result<Vec<LosslessToken>, LexingError> {
+    let mut tokens = Vec::new();
+    let mut lexer = LosslessLexer::new();
+
+    for c in string.chars() {
+        tokens.append(&mut lexer.feed(Some(c))?);
+    }
+    tokens.append(&mut lexer.feed(None)?);
+
+    // The lexer is always ended after feeding `None` without errors
+    assert!(lexer.is_ended());
+
+    Ok(tokens)
+}
+
+// Concatenate the texts of the tokens
+pub fn to_text(tokens: &[LosslessToken]) -> String {
+    tokens.iter().map(LosslessToken::text).collect()
+}
+
+// Drop the trivia, giving what `tokenize` returns
+pub fn to_tokens(tokens: &[LosslessToken]) -> Vec<Token> {
+    tokens.iter().filter_map(LosslessToken::to_token).collect()
+}
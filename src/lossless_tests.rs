@@ -0,0 +1,97 @@
+use crate::differential::{exhaustive_inputs, random_input};
+use crate::lossless::{to_text, to_tokens, tokenize_lossless, LosslessToken};
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{tokenize, ExpressionLexingError, LexingError, OperatorKind};
+
+fn number(value: f64, text: &str) -> LosslessToken {
+    LosslessToken::Number {
+        value,
+        text: text.to_string(),
+    }
+}
+
+fn operator(kind: OperatorKind, text: &str) -> LosslessToken {
+    LosslessToken::Operator {
+        kind,
+        text: text.to_string(),
+    }
+}
+
+fn whitespace(text: &str) -> LosslessToken {
+    LosslessToken::Whitespace(text.to_string())
+}
+
+/// Lossless tokens give back the input, and the tokens of `tokenize` without trivia
+fn assert_lossless(input: &str) {
+    let lossless = tokenize_lossless(input);
+
+    match (&lossless, tokenize(input)) {
+        (Ok(lossless), Ok(tokens)) => {
+            assert_eq!(to_text(lossless), input);
+            assert_eq!(to_tokens(lossless), tokens, "\n input: {:?}\n\n", input);
+        }
+        (Err(error), Err(expected)) => assert_eq!(*error, expected, "\n input: {:?}\n\n", input),
+        (lossless, tokens) => panic!(
+            "\n input: {:?}\n lossless: {:?}\n tokens: {:?}\n\n",
+            input, lossless, tokens
+        ),
+    }
+}
+
+#[test]
+fn test_lossless_tokens() {
+    assert_eq!(
+        tokenize_lossless("  0.50+ 7   *10  "),
+        Ok(vec![
+            whitespace("  "),
+            number(0.5, "0.50"),
+            operator(OperatorKind::Add, "+"),
+            whitespace(" "),
+            number(7.0, "7"),
+            whitespace("   "),
+            operator(OperatorKind::Multiply, "*"),
+            number(10.0, "10"),
+            whitespace("  "),
+        ])
+    );
+}
+
+#[test]
+fn test_lossless_single_number() {
+    assert_eq!(tokenize_lossless("0.000"), Ok(vec![number(0.0, "0.000")]));
+}
+
+#[test]
+fn test_lossless_errors() {
+    assert_eq!(
+        tokenize_lossless("1 2"),
+        Err(LexingError::IncorrectExpression(
+            ExpressionLexingError::ExpectedOperator
+        ))
+    );
+}
+
+#[test]
+fn test_trivia() {
+    let tokens = tokenize_lossless(" 1 ").unwrap();
+    let trivia: Vec<bool> = tokens.iter().map(LosslessToken::is_trivia).collect();
+
+    assert_eq!(trivia, vec![true, false, true]);
+}
+
+#[test]
+fn test_lossless_exhaustive() {
+    for input in exhaustive_inputs(&ALPHABET, 5) {
+        assert_lossless(&input);
+    }
+}
+
+#[test]
+fn test_lossless_random() {
+    seeded::check(
+        5000,
+        || random_input(&ALPHABET, 40),
+        |input| assert_lossless(input),
+    );
+}
@@ -0,0 +1,489 @@
+// A Language Server Protocol server for `.pasta` files, one expression per line, speaking
+// JSON-RPC over any reader and writer. It publishes lexing and evaluation errors as
+// diagnostics, and provides semantic tokens, hovers with the values of constant
+// subexpressions and formatting.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+use std::ops::Range;
+
+use serde_json::{json, Value};
+
+use crate::evaluate::{evaluate, EvaluationError};
+use crate::format::format_text;
+use crate::golden::SpannedError;
+use crate::highlight::{highlight, HighlightKind};
+use crate::incremental::{tokenize_spanned_with, SpannedToken};
+use crate::tokenize::{Lexer, OperatorKind, Token};
+
+// The token types of the semantic tokens legend, indexed by the encoded tokens
+pub const TOKEN_TYPES: [&str; 3] = ["number", "operator", "variable"];
+
+// JSON-RPC error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+// The largest message body read, larger ones are rejected before anything is allocated
+pub const MAX_CONTENT_LENGTH: usize = 16 * 1024 * 1024;
+
+// ====================
+// Framing
+// ====================
+
+// Read the next message, `None` at the end of input. Messages are a JSON body preceded by
+// headers, of which only `Content-Length` is used. An `InvalidData` error is a message which
+// can't be read, skipped so that the next one can be.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut length = Ok(None);
+    let mut headers = false;
+
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return if headers {
+                Err(invalid("end of input in the headers"))
+            } else {
+                Ok(None)
+            };
+        }
+        headers = true;
+
+        let header = header.trim_end_matches(['\r', '\n'].as_ref());
+        if header.is_empty() {
+            break;
+        }
+        // The body of a message without a valid length can't be skipped, it runs into the
+        // headers of the next message
+        let name = "content-length:";
+        if let Some(start) = header.to_ascii_lowercase().find(name) {
+            length = match header[start + name.len()..].trim().parse::<usize>() {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(invalid(header)),
+            };
+        }
+    }
+
+    let length = length?.ok_or_else(|| invalid("missing Content-Length"))?;
+    if length > MAX_CONTENT_LENGTH {
+        // Skipped without allocating it
+        io::copy(&mut reader.by_ref().take(length as u64), &mut io::sink())?;
+        return Err(invalid("Content-Length above the limit"));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| invalid("body is not UTF-8"))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+// ====================
+// Positions
+// ====================
+
+// The lines of a document, without their line endings
+fn lines(text: &str) -> Vec<&str> {
+    text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect()
+}
+
+// LSP columns count UTF-16 code units
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().map(char::len_utf16).sum()
+}
+
+// The byte offset of the column, clamped to the end of the line
+fn offset(line: &str, column: usize) -> usize {
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= column {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+fn range(line_number: usize, line: &str, range: Range<usize>) -> Value {
+    json!({
+        "start": { "line": line_number, "character": column(line, range.start) },
+        "end": { "line": line_number, "character": column(line, range.end) },
+    })
+}
+
+// ====================
+// Analysis
+// ====================
+
+// The tokens of a line with their byte ranges, or its first error
+fn spanned_tokens(line: &str) -> Result<Vec<(Token, Range<usize>)>, SpannedError> {
+    tokenize_spanned_with(Lexer::new(), line)
+        .into_iter()
+        .map(|SpannedToken { token, span }| match token {
+            Ok(token) => Ok((token, span)),
+            Err(error) => Err(SpannedError { error, span }),
+        })
+        .collect()
+}
+
+// The range of the first zero divisor, the one `evaluate` stops at
+fn zero_divisor(tokens: &[(Token, Range<usize>)]) -> Option<Range<usize>> {
+    tokens.windows(2).find_map(|pair| match pair {
+        [(Token::Operator(OperatorKind::Divide), _), (Token::Number(number), range)]
+            if *number == 0.0 =>
+        {
+            Some(range.clone())
+        }
+        _ => None,
+    })
+}
+
+// What the lexer expected where the line fails, e.g. "expected a digit, found 'x'"
+fn expectation(line: &str) -> String {
+    let mut lexer = Lexer::new();
+
+    for c in line.chars().map(Some).chain(std::iter::once(None)) {
+        let expected = lexer.expected_inputs();
+        if lexer.feed(c).is_err() {
+            let found = match c {
+                Some(c) => format!("{:?}", c),
+                None => "the end of input".to_string(),
+            };
+            return format!("expected {}, found {}", expected.describe(), found);
+        }
+    }
+    unreachable!("the line failed to lex")
+}
+
+// The error of the line and its byte range, if any
+fn line_error(line: &str) -> Option<(String, Range<usize>)> {
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    match spanned_tokens(line) {
+        Err(SpannedError { span, .. }) => Some((expectation(line), span)),
+        Ok(tokens) => {
+            let numbers: Vec<Token> = tokens.iter().map(|(token, _)| token.clone()).collect();
+            match evaluate(&numbers) {
+                Err(EvaluationError::DivisionByZero) => {
+                    let range = zero_divisor(&tokens).unwrap_or(0..line.len());
+                    Some((EvaluationError::DivisionByZero.describe(), range))
+                }
+                Err(error) => Some((error.describe(), 0..line.len())),
+                Ok(_) => None,
+            }
+        }
+    }
+}
+
+pub fn diagnostics(text: &str) -> Vec<Value> {
+    lines(text)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(line_number, line)| {
+            let (message, span) = line_error(line)?;
+            Some(json!({
+                "range": range(line_number, line, span),
+                "severity": 1,
+                "source": "pasta",
+                "message": message,
+            }))
+        })
+        .collect()
+}
+
+// Semantic tokens, encoded relative to the previous one as the protocol requires
+pub fn semantic_tokens(text: &str) -> Vec<u32> {
+    let mut data = vec![];
+    let (mut previous_line, mut previous_column) = (0, 0);
+
+    for (line_number, line) in lines(text).into_iter().enumerate() {
+        for span in highlight(line) {
+            let token_type = match span.kind {
+                HighlightKind::Number => 0,
+                HighlightKind::Operator | HighlightKind::Paren => 1,
+                HighlightKind::Identifier => 2,
+                // Errors are reported as diagnostics
+                HighlightKind::Whitespace | HighlightKind::Error => continue,
+            };
+
+            let start = column(line, span.range.start);
+            let length = column(line, span.range.end) - start;
+            if line_number != previous_line {
+                previous_column = 0;
+            }
+            data.extend_from_slice(&[
+                (line_number - previous_line) as u32,
+                (start - previous_column) as u32,
+                length as u32,
+                token_type,
+                0,
+            ]);
+            previous_line = line_number;
+            previous_column = start;
+        }
+    }
+
+    data
+}
+
+// The constant subexpression under the byte offset of a valid line, with its value: the term
+// of a number, a multiplication or a division, the whole expression for an addition or a
+// subtraction
+fn subexpression(line: &str, offset: usize) -> Option<(Range<usize>, f64)> {
+    let tokens = spanned_tokens(line).ok()?;
+    let index = tokens
+        .iter()
+        .position(|(_, range)| range.start <= offset && offset < range.end)?;
+
+    let is_sum = |token: &Token| {
+        matches!(
+            token,
+            Token::Operator(OperatorKind::Add | OperatorKind::Subtract)
+        )
+    };
+    let (start, end) = if is_sum(&tokens[index].0) {
+        (0, tokens.len())
+    } else {
+        let start = tokens[..index]
+            .iter()
+            .rposition(|(token, _)| is_sum(token))
+            .map_or(0, |i| i + 1);
+        let end = tokens[index..]
+            .iter()
+            .position(|(token, _)| is_sum(token))
+            .map_or(tokens.len(), |i| index + i);
+        (start, end)
+    };
+
+    let subexpression = &tokens[start..end];
+    let numbers: Vec<Token> = subexpression
+        .iter()
+        .map(|(token, _)| token.clone())
+        .collect();
+    let value = evaluate(&numbers).ok()?;
+    let range = subexpression[0].1.start..subexpression[subexpression.len() - 1].1.end;
+
+    Some((range, value))
+}
+
+pub fn hover(text: &str, line_number: usize, character: usize) -> Option<Value> {
+    let line = *lines(text).get(line_number)?;
+    let (span, value) = subexpression(line, offset(line, character))?;
+
+    Some(json!({
+        "contents": {
+            "kind": "markdown",
+            "value": format!("`{}` = `{}`", &line[span.clone()], value),
+        },
+        "range": range(line_number, line, span),
+    }))
+}
+
+// The edits formatting the document, none if it has errors
+pub fn formatting(text: &str) -> Vec<Value> {
+    match format_text(text) {
+        Ok(formatted) if formatted != text => {
+            let lines = lines(text);
+            let last = lines.len() - 1;
+            vec![json!({
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": last, "character": column(lines[last], lines[last].len()) },
+                },
+                "newText": formatted,
+            })]
+        }
+        _ => vec![],
+    }
+}
+
+// ====================
+// The server
+// ====================
+
+#[derive(Default)]
+pub struct Server {
+    // The text of the open documents, by URI
+    documents: HashMap<String, String>,
+    shutdown: bool,
+    exited: bool,
+}
+
+fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Whether the client sent `exit`
+    pub fn is_exited(&self) -> bool {
+        self.exited
+    }
+
+    pub fn document(&self, uri: &str) -> Option<&str> {
+        self.documents.get(uri).map(String::as_str)
+    }
+
+    fn capabilities() -> Value {
+        json!({
+            "capabilities": {
+                // Full document synchronization
+                "textDocumentSync": 1,
+                "hoverProvider": true,
+                "documentFormattingProvider": true,
+                "semanticTokensProvider": {
+                    "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                    "full": true,
+                },
+            },
+            "serverInfo": { "name": "pasta-lsp" },
+        })
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Value {
+        let diagnostics = self.document(uri).map(diagnostics).unwrap_or_default();
+        notification(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    // Handle a notification, returning the notifications to send back
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        match method {
+            "exit" => {
+                self.exited = true;
+                vec![]
+            }
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_string(), text.to_string());
+                vec![self.publish_diagnostics(uri)]
+            }
+            "textDocument/didChange" => {
+                // The whole text is sent with full synchronization, the last change wins
+                let changes = params["contentChanges"].as_array();
+                match changes.and_then(|changes| changes.last()?["text"].as_str()) {
+                    Some(text) => {
+                        self.documents.insert(uri.to_string(), text.to_string());
+                        vec![self.publish_diagnostics(uri)]
+                    }
+                    None => vec![],
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                vec![self.publish_diagnostics(uri)]
+            }
+            _ => vec![],
+        }
+    }
+
+    // Handle a request, returning its result or an error code and message
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        if self.shutdown && method != "shutdown" {
+            return Err((INVALID_REQUEST, "the server is shut down".to_string()));
+        }
+        let document = || {
+            let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+            self.document(uri)
+                .ok_or_else(|| (INVALID_PARAMS, format!("unknown document {:?}", uri)))
+        };
+
+        match method {
+            "initialize" => Ok(Self::capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/semanticTokens/full" => {
+                Ok(json!({ "data": semantic_tokens(document()?) }))
+            }
+            "textDocument/hover" => {
+                let position = &params["position"];
+                let line = position["line"].as_u64().unwrap_or_default() as usize;
+                let character = position["character"].as_u64().unwrap_or_default() as usize;
+                Ok(hover(document()?, line, character).unwrap_or(Value::Null))
+            }
+            "textDocument/formatting" => Ok(Value::Array(formatting(document()?))),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method {:?}", method))),
+        }
+    }
+
+    // Handle a message, returning the messages to send back
+    pub fn handle(&mut self, message: &str) -> Vec<Value> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(error) => {
+                return vec![error_response(Value::Null, PARSE_ERROR, &error.to_string())]
+            }
+        };
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // Responses to requests the server never sends
+            None => return vec![],
+        };
+        let params = &message["params"];
+
+        match message.get("id") {
+            Some(id) => match self.request(method, params) {
+                Ok(result) => vec![response(id.clone(), result)],
+                Err((code, error)) => vec![error_response(id.clone(), code, &error)],
+            },
+            None => self.notify(method, params),
+        }
+    }
+
+    // Serve the messages of the reader until `exit` or the end of input
+    pub fn run(&mut self, reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<()> {
+        loop {
+            let replies = match read_message(reader) {
+                Ok(Some(message)) => self.handle(&message),
+                Ok(None) => break,
+                // The message was skipped, the session goes on with the next one
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    vec![error_response(Value::Null, PARSE_ERROR, &error.to_string())]
+                }
+                Err(error) => return Err(error),
+            };
+
+            for reply in replies {
+                write_message(writer, &reply)?;
+            }
+            if self.exited {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use std::io::{BufReader, Cursor};
+
+use serde_json::{json, Value};
+
+use crate::lsp::{
+    diagnostics, formatting, hover, read_message, semantic_tokens, write_message, Server,
+    INVALID_REQUEST, MAX_CONTENT_LENGTH, METHOD_NOT_FOUND, PARSE_ERROR,
+};
+
+const URI: &str = "file:///formulas.pasta";
+
+/// A client scripting a session: the messages are framed, served in one go, and the replies
+/// read back
+#[derive(Default)]
+struct Client {
+    input: Vec<u8>,
+    next_id: u64,
+}
+
+impl Client {
+    fn send(&mut self, message: Value) {
+        write_message(&mut self.input, &message).unwrap();
+    }
+
+    /// Send bytes as they are, framed or not
+    fn send_raw(&mut self, bytes: &[u8]) {
+        self.input.extend_from_slice(bytes);
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        id
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    fn open(&mut self, text: &str) {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": URI, "languageId": "pasta", "version": 1, "text": text },
+            }),
+        );
+    }
+
+    /// Serve the script, returning the replies
+    fn run(self) -> Vec<Value> {
+        let mut output = vec![];
+        Server::new()
+            .run(&mut Cursor::new(self.input), &mut output)
+            .unwrap();
+
+        let mut reader = BufReader::new(output.as_slice());
+        let mut replies = vec![];
+        while let Some(message) = read_message(&mut reader).unwrap() {
+            replies.push(serde_json::from_str(&message).unwrap());
+        }
+        replies
+    }
+}
+
+/// The result of the response to the request
+fn result(replies: &[Value], id: u64) -> &Value {
+    let response = replies.iter().find(|reply| reply["id"] == id).unwrap();
+    &response["result"]
+}
+
+fn position(line: u64, character: u64) -> Value {
+    json!({ "line": line, "character": character })
+}
+
+fn document() -> Value {
+    json!({ "uri": URI })
+}
+
+#[test]
+fn test_session() {
+    let mut client = Client::default();
+    let initialize = client.request("initialize", json!({ "capabilities": {} }));
+    client.notify("initialized", json!({}));
+    client.open("1 + 2 * 3\n4 /  0\n");
+    let tokens = client.request(
+        "textDocument/semanticTokens/full",
+        json!({ "textDocument": document() }),
+    );
+    let hover = client.request(
+        "textDocument/hover",
+        json!({ "textDocument": document(), "position": position(0, 6) }),
+    );
+    let format = client.request(
+        "textDocument/formatting",
+        json!({ "textDocument": document(), "options": { "tabSize": 4, "insertSpaces": true } }),
+    );
+    let shutdown = client.request("shutdown", Value::Null);
+    client.notify("exit", Value::Null);
+    // Never served after `exit`
+    client.request("shutdown", Value::Null);
+
+    let replies = client.run();
+    assert_eq!(replies.len(), 6);
+
+    let capabilities = &result(&replies, initialize)["capabilities"];
+    assert_eq!(capabilities["textDocumentSync"], 1);
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(
+        capabilities["semanticTokensProvider"]["legend"]["tokenTypes"],
+        json!(["number", "operator", "variable"])
+    );
+
+    let published = &replies[1];
+    assert_eq!(published["method"], "textDocument/publishDiagnostics");
+    assert_eq!(published["params"]["uri"], URI);
+    assert_eq!(
+        published["params"]["diagnostics"],
+        json!([{
+            "range": { "start": position(1, 5), "end": position(1, 6) },
+            "severity": 1,
+            "source": "pasta",
+            "message": "division by zero",
+        }])
+    );
+
+    assert_eq!(
+        result(&replies, tokens)["data"],
+        json!([
+            0, 0, 1, 0, 0, 0, 2, 1, 1, 0, 0, 2, 1, 0, 0, 0, 2, 1, 1, 0, 0, 2, 1, 0, 0, 1, 0, 1, 0,
+            0, 0, 2, 1, 1, 0, 0, 3, 1, 0, 0
+        ])
+    );
+    assert_eq!(
+        result(&replies, hover)["contents"]["value"],
+        "`2 * 3` = `6`"
+    );
+    assert_eq!(result(&replies, format)[0]["newText"], "1 + 2 * 3\n4 / 0\n");
+    assert_eq!(result(&replies, shutdown), &Value::Null);
+}
+
+#[test]
+fn test_diagnostics_follow_changes() {
+    let mut client = Client::default();
+    client.open("1 +");
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": "1 + 2" }],
+        }),
+    );
+    client.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": document() }),
+    );
+
+    let replies = client.run();
+    let diagnostics: Vec<&Value> = replies
+        .iter()
+        .map(|reply| &reply["params"]["diagnostics"])
+        .collect();
+
+    assert_eq!(
+        diagnostics[0][0]["message"],
+        "expected a digit or whitespace, found the end of input"
+    );
+    assert_eq!(
+        diagnostics[0][0]["range"],
+        json!({ "start": position(0, 3), "end": position(0, 3) })
+    );
+    assert_eq!(diagnostics[1], &json!([]));
+    assert_eq!(diagnostics[2], &json!([]));
+}
+
+#[test]
+fn test_errors() {
+    let mut server = Server::new();
+
+    let reply = server.handle("{ not json");
+    assert_eq!(reply[0]["error"]["code"], PARSE_ERROR);
+
+    let reply = server.handle(r#"{"jsonrpc":"2.0","id":1,"method":"workspace/symbol"}"#);
+    assert_eq!(reply[0]["error"]["code"], METHOD_NOT_FOUND);
+
+    // Unknown notifications are ignored
+    assert!(server
+        .handle(r#"{"jsonrpc":"2.0","method":"$/cancelRequest"}"#)
+        .is_empty());
+
+    let reply = server.handle(
+        r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///missing.pasta"},"position":{"line":0,"character":0}}}"#,
+    );
+    assert!(reply[0]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("missing.pasta"));
+
+    server.handle(r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#);
+    let reply = server.handle(r#"{"jsonrpc":"2.0","id":4,"method":"initialize"}"#);
+    assert_eq!(reply[0]["error"]["code"], INVALID_REQUEST);
+    assert!(!server.is_exited());
+}
+
+#[test]
+fn test_read_message() {
+    let input = "Content-Length: 2\r\nContent-Type: application/vscode-jsonrpc\r\n\r\n{}\
+                 content-length: 4\r\n\r\nnull";
+    let mut reader = BufReader::new(input.as_bytes());
+
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some("{}"));
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some("null"));
+    assert_eq!(read_message(&mut reader).unwrap(), None);
+
+    assert!(read_message(&mut BufReader::new("Content-Length: 2\r\n".as_bytes())).is_err());
+    assert!(read_message(&mut BufReader::new("\r\n{}".as_bytes())).is_err());
+
+    // Oversized bodies are rejected from their header and skipped
+    let input = format!(
+        "Content-Length: {}\r\n\r\n{}Content-Length: 2\r\n\r\n{{}}",
+        MAX_CONTENT_LENGTH + 1,
+        " ".repeat(MAX_CONTENT_LENGTH + 1)
+    );
+    let mut reader = BufReader::new(input.as_bytes());
+    let error = read_message(&mut reader).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some("{}"));
+    let header = format!("Content-Length: {}\r\n\r\n", usize::MAX);
+    assert!(read_message(&mut BufReader::new(header.as_bytes())).is_err());
+
+    // A body without a valid length runs into the next headers, which are still read
+    let input = "Content-Length: two\r\n\r\n{}Content-Length: 4\r\n\r\nnull";
+    let mut reader = BufReader::new(input.as_bytes());
+    let error = read_message(&mut reader).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some("null"));
+}
+
+#[test]
+fn test_session_goes_on_after_framing_errors() {
+    let mut client = Client::default();
+    client.send_raw(b"\r\n{}");
+    client.send_raw(b"Content-Length: two\r\n\r\n{}");
+    client.send_raw(format!("Content-Length: {}\r\n\r\n", MAX_CONTENT_LENGTH + 1).as_bytes());
+    client.send_raw(" ".repeat(MAX_CONTENT_LENGTH + 1).as_bytes());
+    let shutdown = client.request("shutdown", Value::Null);
+
+    let replies = client.run();
+    let errors: Vec<&Value> = replies
+        .iter()
+        .filter(|reply| reply["id"].is_null())
+        .map(|reply| &reply["error"]["code"])
+        .collect();
+    assert_eq!(errors, vec![&json!(PARSE_ERROR); 3]);
+    assert_eq!(result(&replies, shutdown), &Value::Null);
+}
+
+#[test]
+fn test_diagnostics() {
+    let diagnostics = diagnostics("0.5 * 12\n\n  \n1.24 + 1\r\néé + x");
+    let ranges: Vec<(&Value, &Value)> = diagnostics
+        .iter()
+        .map(|diagnostic| (&diagnostic["message"], &diagnostic["range"]["start"]))
+        .collect();
+
+    assert_eq!(
+        ranges,
+        vec![
+            (
+                &json!("expected a digit, whitespace, an operator or the end of input, found '.'"),
+                &position(3, 1)
+            ),
+            (
+                &json!("expected a digit or whitespace, found 'é'"),
+                &position(4, 0)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_semantic_tokens_count_utf16() {
+    // "𝑥" is two UTF-16 code units
+    assert_eq!(
+        semantic_tokens("𝑥 * 2\n\n(1)"),
+        vec![
+            0, 0, 2, 2, 0, 0, 3, 1, 1, 0, 0, 2, 1, 0, 0, 2, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1, 1, 1,
+            0
+        ]
+    );
+}
+
+#[test]
+fn test_hover() {
+    let text = "10 - 2 * 3 / 4\n1 +";
+    let value = |line, character| {
+        hover(text, line, character).map(|hover| hover["contents"]["value"].clone())
+    };
+
+    assert_eq!(value(0, 0), Some(json!("`10` = `10`")));
+    assert_eq!(value(0, 3), Some(json!("`10 - 2 * 3 / 4` = `8.5`")));
+    assert_eq!(value(0, 7), Some(json!("`2 * 3 / 4` = `1.5`")));
+    assert_eq!(value(0, 13), Some(json!("`2 * 3 / 4` = `1.5`")));
+    // Whitespace, past the end and invalid lines have no hover
+    assert_eq!(value(0, 2), None);
+    assert_eq!(value(0, 20), None);
+    assert_eq!(value(1, 0), None);
+    assert_eq!(value(2, 0), None);
+
+    assert_eq!(
+        hover(text, 0, 9).unwrap()["range"],
+        json!({ "start": position(0, 5), "end": position(0, 14) })
+    );
+}
+
+#[test]
+fn test_formatting() {
+    assert_eq!(formatting("1 + 2\n"), Vec::<Value>::new());
+    assert_eq!(formatting("1 +"), Vec::<Value>::new());
+    assert_eq!(
+        formatting("1+2\n\n0.50"),
+        vec![json!({
+            "range": { "start": position(0, 0), "end": position(2, 4) },
+            "newText": "1 + 2\n\n0.5\n",
+        })]
+    );
+}
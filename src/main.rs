@@ -1,11 +1,126 @@
-mod tokenize;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
 
-#[cfg(test)]
-mod ported_tests;
+use pasta_tcs::golden::{self, CaseResult};
+use pasta_tcs::highlight::{self, Theme};
+use pasta_tcs::{format, tokenize, transitions};
+
+// Run the `.case` files of the directory, printing the failing ones
+fn run_cases(directory: &str, bless: bool) -> ExitCode {
+    let results = match golden::run_case_directory(Path::new(directory), bless) {
+        Ok(results) => results,
+        Err(error) => {
+            eprintln!("{}: {}", directory, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut failed = 0;
+    for (path, result) in results.iter() {
+        match result {
+            CaseResult::Passed => {}
+            CaseResult::Blessed => println!("blessed {}", path.display()),
+            CaseResult::Failed { expected, actual } => {
+                failed += 1;
+                println!("FAILED {}", path.display());
+                println!("  expected: {:?}", expected);
+                println!("  actual:   {:?}", actual);
+            }
+            CaseResult::Invalid(error) => {
+                failed += 1;
+                println!(
+                    "INVALID {}:{}: {:?}",
+                    path.display(),
+                    error.line,
+                    error.kind
+                );
+            }
+        }
+    }
+    println!("{} cases, {} failed", results.len(), failed);
+
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// Print the formatted file, or with `check` only report whether the files are formatted
+fn format_files(paths: &[&str], check: bool) -> ExitCode {
+    let mut success = true;
+
+    for path in paths {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                success = false;
+                continue;
+            }
+        };
+
+        match format::format_text(&text) {
+            Ok(formatted) if check => {
+                if formatted != text {
+                    println!("{} is not formatted", path);
+                    success = false;
+                }
+            }
+            Ok(formatted) => print!("{}", formatted),
+            Err(error) => {
+                eprintln!("{}:{}: {:?}", path, error.line, error.error);
+                success = false;
+            }
+        }
+    }
+
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
 
 #[allow(unused_must_use)]
-fn main() {
-    let tokens = tokenize::tokenize("1.24 +43");
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        // Print the lexer state machine
+        ["--dot"] => print!("{}", transitions::to_dot()),
+        ["--mermaid"] => print!("{}", transitions::to_mermaid()),
+
+        // Run golden cases, `--bless` rewriting the expectations of failing ones
+        ["--cases", directory] => return run_cases(directory, false),
+        ["--cases", directory, "--bless"] => return run_cases(directory, true),
+
+        // Format files of expressions, one per line
+        ["--format", "--check", paths @ ..] if !paths.is_empty() => {
+            return format_files(paths, true)
+        }
+        ["--format", paths @ ..] if !paths.is_empty() => return format_files(paths, false),
+
+        // Highlight an expression for terminals or HTML pages
+        ["--highlight", expression] => {
+            println!(
+                "{}",
+                highlight::render_ansi(expression, &Theme::ansi_dark())
+            )
+        }
+        ["--highlight", "--html", expression] => println!(
+            "{}",
+            highlight::render_html(expression, &Theme::html_classes(""))
+        ),
+
+        _ => {
+            let tokens = tokenize::tokenize("1.24 +43");
+
+            dbg!(tokens);
+        }
+    }
 
-    dbg!(tokens);
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,132 @@
+use std::ops::Range;
+
+use crate::format::LineError;
+use crate::incremental::{tokenize_spanned, SpannedToken};
+
+// Tokenization of texts with one expression per line, as in line-oriented mode, either
+// serially or split at line boundaries across threads. Every line is lexed independently, so
+// the chunks lexed by each thread give the same tokens as the whole text once their spans are
+// moved to the offset of the chunk.
+
+// The tokens of a text with their spans, and its errors with the line they are on
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TokenizedText {
+    pub tokens: Vec<SpannedToken>,
+    pub errors: Vec<LineError>,
+}
+
+// The tokens of a part of the text, with spans and lines counted from 0 at its start
+struct Chunk {
+    tokens: Vec<SpannedToken>,
+    errors: Vec<LineError>,
+    lines: usize,
+}
+
+fn count_newlines(text: &str) -> usize {
+    text.bytes().filter(|&byte| byte == b'\n').count()
+}
+
+fn tokenize_chunk(text: &str) -> Chunk {
+    let tokens = tokenize_spanned(text);
+
+    // Count the lines up to every error, continuing from the previous one
+    let mut errors = vec![];
+    let (mut line, mut counted) = (0, 0);
+    for token in tokens.iter() {
+        if let Err(error) = token.token {
+            line += count_newlines(&text[counted..token.span.start]);
+            counted = token.span.start;
+            errors.push(LineError { line, error });
+        }
+    }
+
+    Chunk {
+        tokens,
+        errors,
+        lines: count_newlines(text),
+    }
+}
+
+// Split the text after newlines into about `count` ranges of similar length
+fn split_lines(text: &str, count: usize) -> Vec<Range<usize>> {
+    let size = text.len().div_ceil(count.max(1)).max(1);
+    let mut ranges = vec![];
+    let mut start = 0;
+
+    while start < text.len() {
+        // Searched in bytes, the middle of a chunk may not be a character boundary
+        let middle = (start + size).min(text.len());
+        let end = match text.as_bytes()[middle..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+        {
+            Some(newline) => middle + newline + 1,
+            None => text.len(),
+        };
+        ranges.push(start..end);
+        start = end;
+    }
+
+    ranges
+}
+
+// Concatenate the chunks in order, moving their spans and lines after the previous ones
+fn merge(chunks: impl IntoIterator<Item = (usize, Chunk)>) -> TokenizedText {
+    let mut merged = TokenizedText::default();
+    // The line the chunk starts at, counted from 1
+    let mut lines = 1;
+
+    for (offset, chunk) in chunks {
+        merged
+            .tokens
+            .extend(chunk.tokens.into_iter().map(|token| SpannedToken {
+                span: token.span.start + offset..token.span.end + offset,
+                ..token
+            }));
+        merged
+            .errors
+            .extend(chunk.errors.into_iter().map(|error| LineError {
+                line: error.line + lines,
+                ..error
+            }));
+        lines += chunk.lines;
+    }
+
+    merged
+}
+
+// Lex every line of the text, lines being counted from 1
+pub fn tokenize_text(text: &str) -> TokenizedText {
+    merge(std::iter::once((0, tokenize_chunk(text))))
+}
+
+// Lex the lines of the text on up to `threads` threads. The result is the same as
+// `tokenize_text`.
+pub fn tokenize_text_parallel(text: &str, threads: usize) -> TokenizedText {
+    let ranges = split_lines(text, threads);
+    if ranges.len() <= 1 {
+        return tokenize_text(text);
+    }
+
+    let chunks: Vec<(usize, Chunk)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = ranges
+            .iter()
+            .map(|range| {
+                let chunk = &text[range.clone()];
+                (range.start, scope.spawn(move || tokenize_chunk(chunk)))
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|(offset, worker)| (offset, worker.join().unwrap()))
+            .collect()
+    });
+
+    merge(chunks)
+}
+
+// The threads `tokenize_text_parallel` can use on this machine
+pub fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |threads| threads.get())
+}
@@ -0,0 +1,86 @@
+use crate::differential::random_input;
+use crate::format::LineError;
+use crate::incremental::tokenize_spanned;
+use crate::parallel::{tokenize_text, tokenize_text_parallel};
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{ExpressionLexingError, LexingError, NumberLexingError};
+
+#[test]
+fn test_error_lines() {
+    let text = "1 + 2\n1.5\n\n3 +\n4 * 5\n";
+
+    for threads in 1..=8 {
+        let tokenized = tokenize_text_parallel(text, threads);
+
+        assert_eq!(tokenized.tokens, tokenize_spanned(text));
+        assert_eq!(
+            tokenized.errors,
+            vec![
+                LineError {
+                    line: 2,
+                    error: LexingError::IncorrectNumber(
+                        NumberLexingError::NonZeroIntegerBeforePoint
+                    ),
+                },
+                LineError {
+                    line: 3,
+                    error: LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber),
+                },
+                LineError {
+                    line: 4,
+                    error: LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber),
+                },
+            ],
+            "\n threads: {}\n\n",
+            threads
+        );
+    }
+}
+
+#[test]
+fn test_edge_cases() {
+    for text in ["", "\n", "\n\n\n", "1", "1 +", "1\n2", "1\n\n", "é\né\n1é"] {
+        let serial = tokenize_text(text);
+        for threads in 0..=4 {
+            assert_eq!(tokenize_text_parallel(text, threads), serial);
+        }
+    }
+}
+
+#[test]
+fn test_parallel_random() {
+    seeded::check(
+        500,
+        || random_input(&ALPHABET, 60),
+        |text| {
+            let serial = tokenize_text(text);
+            assert_eq!(serial.tokens, tokenize_spanned(text));
+
+            for threads in [2, 3, 7] {
+                assert_eq!(
+                    tokenize_text_parallel(text, threads),
+                    serial,
+                    "\n threads: {}\n\n",
+                    threads
+                );
+            }
+        },
+    );
+}
+
+#[test]
+fn test_large_text() {
+    let text: String = (0..20_000)
+        .map(|i| match i % 5 {
+            0 => format!("{} + 0.5\n", i),
+            1 => "1.5 - 2\n".to_string(),
+            _ => format!("{} * {}\n", i, i + 1),
+        })
+        .collect();
+
+    let serial = tokenize_text(&text);
+    assert_eq!(serial.errors.len(), 4000);
+    assert_eq!(serial.errors[1].line, 7);
+    assert_eq!(tokenize_text_parallel(&text, 8), serial);
+}
@@ -0,0 +1,50 @@
+// Run the ported tests against every token source
+
+macro_rules! ported_suite {
+    ($name:ident, $type:ty, $source:expr) => {
+        mod $name {
+            /// The implementation under test, built once for the whole suite
+            fn source() -> &'static std::sync::Mutex<$type> {
+                static SOURCE: std::sync::OnceLock<std::sync::Mutex<$type>> =
+                    std::sync::OnceLock::new();
+                SOURCE.get_or_init(|| {
+                    let source: $type = $source;
+                    std::sync::Mutex::new(source)
+                })
+            }
+
+            fn tokenize(
+                string: &str,
+            ) -> Result<Vec<crate::tokenize::Token>, crate::tokenize::LexingError> {
+                // A test failing while holding the lock leaves the source usable
+                let mut source = source()
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                crate::token_source::TokenSource::tokenize(&mut *source, string)
+            }
+
+            include!("ported_tests.rs");
+        }
+    };
+}
+
+ported_suite!(
+    hand_written,
+    crate::tokenize::Lexer,
+    crate::tokenize::Lexer::with_backend(crate::tokenize::Backend::HandWritten)
+);
+ported_suite!(
+    table_driven,
+    crate::tokenize::Lexer,
+    crate::tokenize::Lexer::with_backend(crate::tokenize::Backend::TableDriven)
+);
+ported_suite!(
+    generated_spec,
+    crate::spec::CompiledSpec,
+    crate::spec_lexer::generated_spec().clone()
+);
+ported_suite!(
+    tokenize_function,
+    fn(&str) -> Result<Vec<crate::tokenize::Token>, crate::tokenize::LexingError>,
+    crate::tokenize::tokenize
+);
@@ -1,99 +1,27 @@
 use rand::Rng;
 
+use crate::seeded::{self, TestRng};
+use crate::testing::{Generator, GeneratorConfig};
+
+// `tokenize` is defined by the module including these tests, see `ported_suites.rs`
 use crate::tokenize::{
-    tokenize, ExpressionLexingError, LexingError, NumberLexingError, OperatorKind, Token,
+    get_operator_kind, ExpressionLexingError, LexingError, NumberLexingError, OperatorKind, Token,
 };
 
-/// The number of times to repeat each test, for more stability
-const REPEAT_COUNT: i32 = 1000;
-
-/// If something went wrong with generating the test cases
-macro_rules! bad_test {
-    () => {
-        panic!("Unknown error in the test (Please contact course coordinator)");
-    };
-}
+/// The number of generated inputs each test is checked on
+const CHECK_COUNT: u32 = 1000;
 
 fn assert_eq_with_input<T: std::fmt::Debug + PartialEq>(input: &str, expected: &T, actual: &T) {
     assert_eq!(expected, actual, "\n input: \"{}\"\n\n", input);
 }
 
-fn digit_to_char(digit: i32) -> char {
-    match digit {
-        0 => '0',
-        1 => '1',
-        2 => '2',
-        3 => '3',
-        4 => '4',
-        5 => '5',
-        6 => '6',
-        7 => '7',
-        8 => '8',
-        9 => '9',
-        _ => bad_test!(),
-    }
-}
-
-fn random_non_zero_digit() -> char {
-    let mut rng = rand::thread_rng();
-    let digit = rng.gen_range(1..10);
-    digit_to_char(digit)
-}
-
-fn random_digit() -> char {
-    let mut rng = rand::thread_rng();
-    let digit = rng.gen_range(0..10);
-    digit_to_char(digit)
-}
-
-fn random_integer_string(len: Option<i32>) -> String {
-    let len = if let Some(len) = len {
-        len
-    } else {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(1..5)
-    };
-
-    (0..len)
-        .map(|i| {
-            if i == 0 {
-                random_non_zero_digit()
-            } else {
-                random_digit()
-            }
-        })
-        .collect()
-}
-
-fn random_decimal_string(len: Option<i32>) -> String {
-    format!("0.{}", random_integer_string(len))
+/// The shared generator of test inputs, drawing from the seeded generator of the thread
+fn generator() -> Generator<TestRng> {
+    Generator::new(seeded::thread_rng(), GeneratorConfig::default()).unwrap()
 }
 
 fn random_number_token() -> Token {
-    let mut rng = rand::thread_rng();
-    let is_decimal = rng.gen_bool(0.5);
-
-    if is_decimal {
-        as_number_token(&random_decimal_string(None))
-    } else {
-        as_number_token(&random_integer_string(None))
-    }
-}
-
-fn random_operator() -> OperatorKind {
-    let mut rng = rand::thread_rng();
-    let operator = rng.gen_range(0..4);
-    match operator {
-        0 => OperatorKind::Subtract,
-        1 => OperatorKind::Add,
-        2 => OperatorKind::Divide,
-        3 => OperatorKind::Multiply,
-        _ => bad_test!(),
-    }
-}
-
-fn random_operator_token() -> Token {
-    Token::Operator(random_operator())
+    as_number_token(&generator().number_string())
 }
 
 fn as_number_token(str: &str) -> Token {
@@ -103,278 +31,245 @@ fn as_number_token(str: &str) -> Token {
 fn token_to_string(token: &Token) -> String {
     match token {
         Token::Number(num) => num.to_string(),
+        Token::Decimal(decimal) => decimal.to_string(),
+        Token::Integer(integer) => integer.to_string(),
         Token::Operator(op) => match op {
             OperatorKind::Add => "+".to_string(),
             OperatorKind::Subtract => "-".to_string(),
             OperatorKind::Multiply => "*".to_string(),
             OperatorKind::Divide => "/".to_string(),
         },
+        Token::EndOfExpression => "\n".to_string(),
     }
 }
 
-fn pad_with_random_whitespaces(strings: &mut Vec<String>) {
-    if strings.len() > 1 {
-        let mut rng = rand::thread_rng();
-        for _ in 0..(strings.len() - 1) {
-            let pos = rng.gen_range(1..strings.len());
-            strings.insert(pos, " ".to_string());
-        }
-    }
-}
+fn token_list_to_string(tokens: &[Token]) -> String {
+    let strings: Vec<String> = tokens.iter().map(token_to_string).collect();
 
-fn token_list_to_string_list(tokens: &Vec<Token>) -> Vec<String> {
-    tokens.iter().map(|t| token_to_string(t)).collect()
+    generator().pad_with_random_whitespaces(&strings)
 }
 
-fn token_list_to_string(tokens: &Vec<Token>) -> String {
-    let mut strings: Vec<String> = token_list_to_string_list(tokens);
-
-    pad_with_random_whitespaces(&mut strings);
-
-    strings.join("")
-}
+fn random_valid_token_sequence_with_replaced_number(replacement: String) -> String {
+    let mut generator = generator();
+    let mut strings = generator.valid_lexemes();
 
-fn random_valid_token_sequence(operator_count: Option<i32>) -> Vec<Token> {
-    let operator_count = if let Some(operator_count) = operator_count {
-        operator_count
-    } else {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(1..5)
-    };
+    // Numbers are at even positions
+    let index = 2 * seeded::thread_rng().gen_range(0..strings.len().div_ceil(2));
+    strings[index] = replacement;
 
-    let mut tokens = vec![];
-    for i in 0..operator_count {
-        if i != 0 {
-            tokens.push(random_operator_token());
+    generator.pad_with_random_whitespaces(&strings)
+}
+
+/// The lexemes of the input if it is shaped like an expression: numbers made of digits and
+/// points, alternating with operators, with any spaces around them
+fn lexemes(input: &str) -> Option<Vec<&str>> {
+    let mut lexemes = vec![];
+    let mut rest = input.trim_start_matches(' ');
+
+    while !rest.is_empty() {
+        let end = if lexemes.len() % 2 == 0 {
+            rest.find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len())
+        } else if rest.starts_with(|c| get_operator_kind(c).is_some()) {
+            1
+        } else {
+            0
+        };
+        if end == 0 {
+            return None;
         }
-        tokens.push(random_number_token());
+
+        lexemes.push(&rest[..end]);
+        rest = rest[end..].trim_start_matches(' ');
     }
 
-    tokens
+    if lexemes.len() % 2 == 1 {
+        Some(lexemes)
+    } else {
+        None
+    }
 }
 
-fn random_even_number_under(mut before: usize) -> usize {
-    let mut rng = rand::thread_rng();
-    if before % 2 == 1 {
-        before += 1;
+/// Whether the lexeme is a number the lexer accepts
+fn is_valid_number(lexeme: &str) -> bool {
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    match lexeme.strip_prefix("0.") {
+        Some(decimals) => is_digits(decimals),
+        None => lexeme == "0" || (is_digits(lexeme) && !lexeme.starts_with('0')),
     }
-    before /= 2;
-    let number = rng.gen_range(0..before);
-    number * 2
 }
 
-fn random_valid_token_sequence_with_replaced_number(replacement: String) -> String {
-    let seq = random_valid_token_sequence(None);
-    let mut strings = token_list_to_string_list(&seq);
-
-    let index = random_even_number_under(strings.len());
-    strings[index] = replacement;
+/// The tokens of a valid expression, `None` for any other input
+fn expected_tokens(input: &str) -> Option<Vec<Token>> {
+    lexemes(input)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, lexeme)| match i % 2 {
+            0 if is_valid_number(lexeme) => Some(as_number_token(lexeme)),
+            0 => None,
+            _ => lexeme.chars().next().and_then(get_operator_kind).map(Token::Operator),
+        })
+        .collect()
+}
 
-    pad_with_random_whitespaces(&mut strings);
-    let string = strings.join("");
+/// Whether the input is a valid expression but for one number, which `is_invalid` accepts
+fn has_one_invalid_number(input: &str, is_invalid: fn(&str) -> bool) -> bool {
+    let numbers = match lexemes(input) {
+        Some(lexemes) => lexemes.into_iter().step_by(2),
+        None => return false,
+    };
+    let invalid: Vec<&str> = numbers.filter(|number| !is_valid_number(number)).collect();
 
-    string
+    matches!(invalid.as_slice(), [number] if is_invalid(number))
 }
 
-/// Helper function to repeat a test multiple times
-/// Students have had issues with tests sometimes passing and sometimes failing
-/// This function will help to make sure that the result is more stable
-fn repeat(test: impl Fn()) {
-    for _ in 0..REPEAT_COUNT {
-        test();
-    }
+/// Check that the expressions of the generated tokens are lexed back to them. Every run is
+/// seeded and failures are shrunk, see `seeded.rs` to replay one.
+fn check_valid(mut generate: impl FnMut() -> Vec<Token>) {
+    seeded::check(
+        CHECK_COUNT,
+        || {
+            let tokens = generate();
+            let string = token_list_to_string(&tokens);
+            assert_eq!(expected_tokens(&string), Some(tokens), "\n input: {:?}", string);
+            string
+        },
+        |string| {
+            // The tokens are only known for valid expressions, which shrinking can leave
+            if let Some(tokens) = expected_tokens(string) {
+                assert_eq_with_input(string, &tokenize(string), &Ok(tokens));
+            }
+        },
+    );
+}
+
+/// Check that valid expressions with one generated invalid number fail with the error
+fn check_invalid(
+    mut generate: impl FnMut() -> String,
+    is_invalid: fn(&str) -> bool,
+    error: NumberLexingError,
+) {
+    seeded::check(
+        CHECK_COUNT,
+        || {
+            let string = random_valid_token_sequence_with_replaced_number(generate());
+            assert!(has_one_invalid_number(&string, is_invalid), "\n input: {:?}", string);
+            string
+        },
+        |string| {
+            if has_one_invalid_number(string, is_invalid) {
+                let expected = Err(LexingError::IncorrectNumber(error));
+                assert_eq_with_input(string, &tokenize(string), &expected);
+            }
+        },
+    );
 }
 
 #[test]
 fn test_analyze_single_digit_integer() {
-    repeat(|| {
-        let number = random_integer_string(Some(1));
-        let tokens = vec![as_number_token(&number)];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+    check_valid(|| vec![as_number_token(&generator().integer_string(Some(1)))]);
 }
 
 #[test]
 fn test_analyze_multi_digit_integer() {
-    repeat(|| {
-        let number = random_integer_string(None);
-        let tokens = vec![as_number_token(&number)];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+    check_valid(|| vec![as_number_token(&generator().integer_string(None))]);
 }
 
 #[test]
 fn test_analyze_single_digit_decimal() {
-    repeat(|| {
-        let number = random_decimal_string(Some(1));
-        let tokens = vec![as_number_token(&number)];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+    check_valid(|| vec![as_number_token(&generator().decimal_string(Some(1)))]);
 }
 
 #[test]
 fn test_analyze_multi_digit_decimal() {
-    repeat(|| {
-        let number = random_decimal_string(None);
-        let tokens = vec![as_number_token(&number)];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+    check_valid(|| vec![as_number_token(&generator().decimal_string(None))]);
 }
 
 #[test]
 fn test_non_zero_integer_part() {
-    repeat(|| {
-        let invalid_number = format!(
-            "{}.{}",
-            random_integer_string(None),
-            random_integer_string(None)
-        );
-        let string = random_valid_token_sequence_with_replaced_number(invalid_number);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(
-            &string,
-            &output,
-            &Err(LexingError::IncorrectNumber(
-                NumberLexingError::NonZeroIntegerBeforePoint,
-            )),
-        );
-    })
+    check_invalid(
+        || {
+            format!(
+                "{}.{}",
+                generator().integer_string(None),
+                generator().integer_string(None)
+            )
+        },
+        |number| matches!(number.split_once('.'), Some((integer, _)) if is_valid_number(integer) && integer != "0"),
+        NumberLexingError::NonZeroIntegerBeforePoint,
+    );
 }
 
 #[test]
 fn test_decimal_without_integer_part() {
-    repeat(|| {
-        let invalid_number = format!(".{}", random_integer_string(None));
-        let string = random_valid_token_sequence_with_replaced_number(invalid_number);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(
-            &string,
-            &output,
-            &Err(LexingError::IncorrectNumber(
-                NumberLexingError::MissingIntegerBeforePoint,
-            )),
-        );
-    })
+    check_invalid(
+        || format!(".{}", generator().integer_string(None)),
+        |number| number.starts_with('.'),
+        NumberLexingError::MissingIntegerBeforePoint,
+    );
 }
 
 #[test]
 fn test_integer_starting_with_zero() {
-    repeat(|| {
-        let invalid_number = format!("0{}", random_integer_string(None));
-        let string = random_valid_token_sequence_with_replaced_number(invalid_number);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(
-            &string,
-            &output,
-            &Err(LexingError::IncorrectNumber(
-                NumberLexingError::ExpectedPointAfterZero,
-            )),
-        );
-    })
+    check_invalid(
+        || format!("0{}", generator().integer_string(None)),
+        |number| number.starts_with('0') && number[1..].starts_with(|c: char| c.is_ascii_digit()),
+        NumberLexingError::ExpectedPointAfterZero,
+    );
 }
 
 #[test]
 fn test_decimal_without_decimal_part() {
-    repeat(|| {
-        let invalid_number = "0.".to_string();
-        let string = random_valid_token_sequence_with_replaced_number(invalid_number);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(
-            &string,
-            &output,
-            &Err(LexingError::IncorrectNumber(
-                NumberLexingError::ExpectedDigitAfterPoint,
-            )),
-        );
-    })
+    check_invalid(
+        || "0.".to_string(),
+        |number| number == "0.",
+        NumberLexingError::ExpectedDigitAfterPoint,
+    );
 }
 
 #[test]
 fn test_simple_plus() {
-    repeat(|| {
-        let tokens = vec![
+    check_valid(|| {
+        vec![
             random_number_token(),
             Token::Operator(OperatorKind::Add),
             random_number_token(),
-        ];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+        ]
+    });
 }
 
 #[test]
 fn test_simple_minus() {
-    repeat(|| {
-        let tokens = vec![
+    check_valid(|| {
+        vec![
             random_number_token(),
             Token::Operator(OperatorKind::Subtract),
             random_number_token(),
-        ];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+        ]
+    });
 }
 
 #[test]
 fn test_simple_multiply() {
-    repeat(|| {
-        let tokens = vec![
+    check_valid(|| {
+        vec![
             random_number_token(),
             Token::Operator(OperatorKind::Multiply),
             random_number_token(),
-        ];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+        ]
+    });
 }
 
 #[test]
 fn test_simple_divide() {
-    repeat(|| {
-        let tokens = vec![
+    check_valid(|| {
+        vec![
             random_number_token(),
             Token::Operator(OperatorKind::Divide),
             random_number_token(),
-        ];
-        let string = token_list_to_string(&tokens);
-
-        let output = tokenize(&string);
-
-        assert_eq_with_input(&string, &output, &Ok(tokens));
-    })
+        ]
+    });
 }
 
 #[test]
@@ -0,0 +1,275 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read};
+use std::str::Utf8Error;
+
+use crate::tokenize::{tokenize_with, EncodingLexingError, Lexer, LexingError, Token};
+
+// The number of bytes requested from the reader at a time
+pub(crate) const CHUNK_SIZE: usize = 8 * 1024;
+
+// ====================
+// Errors
+// ====================
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Lexing(LexingError),
+}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+impl From<LexingError> for ReadError {
+    fn from(error: LexingError) -> Self {
+        ReadError::Lexing(error)
+    }
+}
+
+// Convert a UTF-8 error for bytes starting at `offset` in the stream to a lexing error
+fn encoding_error(error: Utf8Error, offset: usize) -> LexingError {
+    let offset = offset + error.valid_up_to();
+    let error = match error.error_len() {
+        Some(len) => EncodingLexingError::InvalidUtf8 { offset, len },
+        None => EncodingLexingError::IncompleteUtf8 { offset },
+    };
+
+    LexingError::IncorrectEncoding(error)
+}
+
+// ====================
+// Incremental UTF-8 decoder
+// ====================
+
+struct Utf8Decoder {
+    // Undecoded bytes, at most the start of a single character split across two reads
+    pending: Vec<u8>,
+    // Offset of the first pending byte in the stream
+    offset: usize,
+}
+
+impl Utf8Decoder {
+    fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    // Decode `bytes`, calling `f` for every complete character.
+    // Bytes of an incomplete trailing character are kept until the next call.
+    fn decode(
+        &mut self,
+        bytes: &[u8],
+        mut f: impl FnMut(char) -> Result<(), LexingError>,
+    ) -> Result<(), LexingError> {
+        self.pending.extend_from_slice(bytes);
+
+        let (valid, result) = match std::str::from_utf8(&self.pending) {
+            Ok(string) => (string.len(), Ok(())),
+            Err(error) => match error.error_len() {
+                // A character is cut off by the end of the chunk, keep it for later
+                None => (error.valid_up_to(), Ok(())),
+                Some(_) => (error.valid_up_to(), Err(encoding_error(error, self.offset))),
+            },
+        };
+
+        // The prefix was already validated above
+        let string = std::str::from_utf8(&self.pending[..valid]).unwrap();
+        for c in string.chars() {
+            f(c)?;
+        }
+        result?;
+
+        self.pending.drain(..valid);
+        self.offset += valid;
+        Ok(())
+    }
+
+    // Make sure no bytes were left over at the end of the stream
+    fn finish(&self) -> Result<(), LexingError> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err(LexingError::IncorrectEncoding(
+                EncodingLexingError::IncompleteUtf8 {
+                    offset: self.offset,
+                },
+            ))
+        }
+    }
+}
+
+// ====================
+// Lexer fed with bytes
+// ====================
+
+// Decodes bytes and feeds the characters to a lexer, collecting the emitted tokens
+pub(crate) struct ByteLexer {
+    lexer: Lexer,
+    decoder: Utf8Decoder,
+    pub(crate) tokens: VecDeque<Token>,
+}
+
+impl ByteLexer {
+    pub(crate) fn new() -> Self {
+        Self {
+            lexer: Lexer::new(),
+            decoder: Utf8Decoder::new(),
+            tokens: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn feed_bytes(&mut self, bytes: &[u8]) -> Result<(), LexingError> {
+        let lexer = &mut self.lexer;
+        let tokens = &mut self.tokens;
+
+        self.decoder.decode(bytes, |c| {
+            tokens.extend(lexer.feed(Some(c))?);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn feed_end(&mut self) -> Result<(), LexingError> {
+        self.decoder.finish()?;
+        self.tokens.extend(self.lexer.feed(None)?);
+        Ok(())
+    }
+}
+
+// ====================
+// Token stream over a reader
+// ====================
+
+pub struct ReaderTokens<R> {
+    reader: R,
+    lexer: ByteLexer,
+    chunk: Vec<u8>,
+    error: Option<ReadError>,
+    done: bool,
+}
+
+impl<R: Read> ReaderTokens<R> {
+    // Read the next chunk and feed it to the lexer
+    fn advance(&mut self) -> Result<(), ReadError> {
+        let read = loop {
+            match self.reader.read(&mut self.chunk) {
+                Ok(read) => break read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        };
+
+        if read == 0 {
+            // == EOI ==
+            self.done = true;
+            self.lexer.feed_end()?;
+        } else {
+            self.lexer.feed_bytes(&self.chunk[..read])?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for ReaderTokens<R> {
+    type Item = Result<Token, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Tokens emitted before an error are returned first
+            if let Some(token) = self.lexer.tokens.pop_front() {
+                return Some(Ok(token));
+            }
+            if let Some(error) = self.error.take() {
+                return Some(Err(error));
+            }
+            if self.done {
+                return None;
+            }
+
+            if let Err(error) = self.advance() {
+                self.error = Some(error);
+                self.done = true;
+            }
+        }
+    }
+}
+
+// Lex the whole reader as a single expression, reading it in chunks
+pub fn tokenize_reader<R: Read>(reader: R) -> ReaderTokens<R> {
+    ReaderTokens {
+        reader,
+        lexer: ByteLexer::new(),
+        chunk: vec![0; CHUNK_SIZE],
+        error: None,
+        done: false,
+    }
+}
+
+// ====================
+// Line stream over a buffered reader
+// ====================
+
+pub struct ReaderLines<R> {
+    reader: R,
+    // Reused for every line
+    lexer: Lexer,
+    line: Vec<u8>,
+    // Offset of the next line in the stream
+    offset: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for ReaderLines<R> {
+    type Item = Result<Vec<Token>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        self.line.clear();
+        let read = match self.reader.read_until(b'\n', &mut self.line) {
+            Ok(0) => {
+                self.done = true;
+                return None;
+            }
+            Ok(read) => read,
+            Err(error) => {
+                self.done = true;
+                return Some(Err(error.into()));
+            }
+        };
+
+        let offset = self.offset;
+        self.offset += read;
+
+        // Strip the line ending, either "\n" or "\r\n"
+        let mut bytes = &self.line[..];
+        if let Some(rest) = bytes.strip_suffix(b"\n") {
+            bytes = rest.strip_suffix(b"\r").unwrap_or(rest);
+        }
+
+        let string = match std::str::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(error) => return Some(Err(encoding_error(error, offset).into())),
+        };
+
+        Some(tokenize_with(&mut self.lexer, string).map_err(ReadError::Lexing))
+    }
+}
+
+// Lex every line of the reader as a separate expression
+pub fn tokenize_lines<R: BufRead>(reader: R) -> ReaderLines<R> {
+    ReaderLines {
+        reader,
+        lexer: Lexer::new(),
+        line: Vec::new(),
+        offset: 0,
+        done: false,
+    }
+}
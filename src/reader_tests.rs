@@ -0,0 +1,155 @@
+use std::io::{self, Cursor, Read};
+
+use crate::reader::{tokenize_lines, tokenize_reader, ReadError};
+use crate::tokenize::{
+    tokenize, EncodingLexingError, ExpressionLexingError, LexingError, OperatorKind, Token,
+};
+
+/// A reader that returns at most one byte per read, to split characters across reads
+struct ByteByByte<'a>(&'a [u8]);
+
+impl<'a> Read for ByteByByte<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.split_first() {
+            Some((byte, rest)) if !buf.is_empty() => {
+                buf[0] = *byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+/// A reader that fails after returning its contents
+struct FailingReader<'a>(&'a [u8]);
+
+impl<'a> Read for FailingReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(io::Error::other("broken pipe"));
+        }
+        let read = self.0.read(buf)?;
+        Ok(read)
+    }
+}
+
+/// Split the results into the successful tokens and the final error, if any
+fn collect<I: Iterator<Item = Result<Token, ReadError>>>(
+    iter: I,
+) -> (Vec<Token>, Option<ReadError>) {
+    let mut tokens = vec![];
+    for result in iter {
+        match result {
+            Ok(token) => tokens.push(token),
+            Err(error) => return (tokens, Some(error)),
+        }
+    }
+    (tokens, None)
+}
+
+fn lexing_error(error: Option<ReadError>) -> Option<LexingError> {
+    match error {
+        Some(ReadError::Lexing(error)) => Some(error),
+        Some(ReadError::Io(error)) => panic!("unexpected io error: {}", error),
+        None => None,
+    }
+}
+
+#[test]
+fn test_reader_matches_tokenize() {
+    let inputs = vec!["0.24 +43", " 0.5 * 2 - 100 / 0.25 ", "7"];
+
+    for input in inputs.into_iter() {
+        let (tokens, error) = collect(tokenize_reader(input.as_bytes()));
+
+        assert!(error.is_none(), "\n input: \"{}\"\n\n", input);
+        assert_eq!(Ok(tokens), tokenize(input), "\n input: \"{}\"\n\n", input);
+    }
+}
+
+#[test]
+fn test_reader_with_split_characters() {
+    let input = "12 + 0.5 é";
+    let (tokens, error) = collect(tokenize_reader(ByteByByte(input.as_bytes())));
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Number(12.0),
+            Token::Operator(OperatorKind::Add),
+            Token::Number(0.5),
+        ]
+    );
+    assert_eq!(
+        lexing_error(error),
+        Some(LexingError::IncorrectExpression(
+            ExpressionLexingError::UnexpectedCharacter('é')
+        ))
+    );
+}
+
+#[test]
+fn test_reader_invalid_utf8() {
+    let (tokens, error) = collect(tokenize_reader(&b"12 + \xff3"[..]));
+
+    assert_eq!(
+        tokens,
+        vec![Token::Number(12.0), Token::Operator(OperatorKind::Add)]
+    );
+    assert_eq!(
+        lexing_error(error),
+        Some(LexingError::IncorrectEncoding(
+            EncodingLexingError::InvalidUtf8 { offset: 5, len: 1 }
+        ))
+    );
+}
+
+#[test]
+fn test_reader_incomplete_utf8() {
+    let (_, error) = collect(tokenize_reader(ByteByByte(b"1 + 2\xc3")));
+
+    assert_eq!(
+        lexing_error(error),
+        Some(LexingError::IncorrectEncoding(
+            EncodingLexingError::IncompleteUtf8 { offset: 5 }
+        ))
+    );
+}
+
+#[test]
+fn test_reader_io_error() {
+    let (tokens, error) = collect(tokenize_reader(FailingReader(b"1 +")));
+
+    assert_eq!(
+        tokens,
+        vec![Token::Number(1.0), Token::Operator(OperatorKind::Add)]
+    );
+    assert!(matches!(error, Some(ReadError::Io(_))));
+}
+
+#[test]
+fn test_lines() {
+    let input = Cursor::new(&b"1 + 2\r\n0.5*3\n1 +\n\xe2\x82 4\n4"[..]);
+    let results: Vec<_> = tokenize_lines(input)
+        .map(|result| match result {
+            Ok(tokens) => Ok(tokens),
+            Err(error) => Err(lexing_error(Some(error)).unwrap()),
+        })
+        .collect();
+
+    assert_eq!(
+        results,
+        vec![
+            tokenize("1 + 2"),
+            tokenize("0.5*3"),
+            Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::ExpectedNumber
+            )),
+            Err(LexingError::IncorrectEncoding(
+                EncodingLexingError::InvalidUtf8 { offset: 17, len: 2 }
+            )),
+            tokenize("4"),
+        ]
+    );
+}
@@ -0,0 +1,150 @@
+// A reference tokenizer built from regular expressions, written independently of the `State` machine.
+// The input is read as a sequence of numbers and operators. At each position, the rules of the
+// current mode are tried in order and the first matching rule decides the outcome.
+
+use regex::Regex;
+
+use crate::token_source::TokenSource;
+use crate::tokenize::{ExpressionLexingError, LexingError, NumberLexingError, OperatorKind, Token};
+
+/// What a matching rule does
+#[derive(Copy, Clone)]
+enum Outcome {
+    /// Emit the number in group 1, then expect an operator
+    Number,
+    /// Emit the operator in group 1, then expect a number
+    Operator,
+    /// The input was fully read
+    Done,
+    Fail(LexingError),
+    /// Fail with an unexpected character error for the character in group 1
+    FailOnCharacter,
+}
+
+pub struct ReferenceLexer {
+    number_rules: Vec<(Regex, Outcome)>,
+    operator_rules: Vec<(Regex, Outcome)>,
+}
+
+fn rules(rules: Vec<(&str, Outcome)>) -> Vec<(Regex, Outcome)> {
+    rules
+        .into_iter()
+        .map(|(pattern, outcome)| (Regex::new(pattern).unwrap(), outcome))
+        .collect()
+}
+
+fn number_error(error: NumberLexingError) -> Outcome {
+    Outcome::Fail(LexingError::IncorrectNumber(error))
+}
+
+fn expression_error(error: ExpressionLexingError) -> Outcome {
+    Outcome::Fail(LexingError::IncorrectExpression(error))
+}
+
+impl ReferenceLexer {
+    pub fn new() -> Self {
+        const NUMBER: &str = r"(?:0\.[0-9]+|0|[1-9][0-9]*)";
+
+        // Expecting a number, after any leading whitespace
+        let number_rules = rules(vec![
+            (
+                r"^ *$",
+                expression_error(ExpressionLexingError::ExpectedNumber),
+            ),
+            (&format!(r"^ *({})(?:[ +\-*/]|$)", NUMBER), Outcome::Number),
+            (
+                r"^ *(?:0\.[0-9]+|[1-9][0-9]*)\.",
+                number_error(NumberLexingError::NonZeroIntegerBeforePoint),
+            ),
+            (
+                r"^ *0\.(?:[^0-9]|$)",
+                number_error(NumberLexingError::ExpectedDigitAfterPoint),
+            ),
+            (
+                r"^ *0[0-9]",
+                number_error(NumberLexingError::ExpectedPointAfterZero),
+            ),
+            (
+                r"^ *\.",
+                number_error(NumberLexingError::MissingIntegerBeforePoint),
+            ),
+            (
+                r"^ *[+\-*/]",
+                expression_error(ExpressionLexingError::ExpectedNumber),
+            ),
+            (&format!(r"(?s)^ *{}(.)", NUMBER), Outcome::FailOnCharacter),
+            (r"(?s)^ *(.)", Outcome::FailOnCharacter),
+        ]);
+
+        // Expecting an operator, right after a number
+        let operator_rules = rules(vec![
+            (r"^ *$", Outcome::Done),
+            (r"^ *([+\-*/])", Outcome::Operator),
+            (
+                r"^ +[0-9]",
+                expression_error(ExpressionLexingError::ExpectedOperator),
+            ),
+            (r"(?s)^ *(.)", Outcome::FailOnCharacter),
+        ]);
+
+        Self {
+            number_rules,
+            operator_rules,
+        }
+    }
+
+    pub fn tokenize(&self, string: &str) -> Result<Vec<Token>, LexingError> {
+        let mut tokens = vec![];
+        let mut rest = string;
+        let mut expect_number = true;
+
+        loop {
+            let rules = if expect_number {
+                &self.number_rules
+            } else {
+                &self.operator_rules
+            };
+
+            let (captures, outcome) = rules
+                .iter()
+                .find_map(|(regex, outcome)| regex.captures(rest).map(|c| (c, *outcome)))
+                .expect("the last rule matches everything");
+            let group = captures.get(1);
+
+            match outcome {
+                Outcome::Number => {
+                    let number = group.unwrap();
+                    tokens.push(Token::Number(number.as_str().parse().unwrap()));
+                    rest = &rest[number.end()..];
+                    expect_number = false;
+                }
+                Outcome::Operator => {
+                    let operator = group.unwrap();
+                    let kind = match operator.as_str() {
+                        "+" => OperatorKind::Add,
+                        "-" => OperatorKind::Subtract,
+                        "*" => OperatorKind::Multiply,
+                        _ => OperatorKind::Divide,
+                    };
+                    tokens.push(Token::Operator(kind));
+                    rest = &rest[operator.end()..];
+                    expect_number = true;
+                }
+                Outcome::Done => return Ok(tokens),
+                Outcome::Fail(error) => return Err(error),
+                Outcome::FailOnCharacter => {
+                    let c = group.unwrap().as_str().chars().next().unwrap();
+                    return Err(LexingError::IncorrectExpression(
+                        ExpressionLexingError::UnexpectedCharacter(c),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl TokenSource for ReferenceLexer {
+    fn tokenize(&mut self, string: &str) -> Result<Vec<Token>, LexingError> {
+        ReferenceLexer::tokenize(self, string)
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::OnceLock;
+
+// Bulk scanning of the runs of characters the lexer stays in the same state for: the digits of
+// a number, and the spaces before a number or an operator. On x86_64 the bytes are compared 32
+// or 16 at a time with AVX2 or SSE2 when the CPU supports them, and one at a time otherwise.
+
+// The implementation of the scans, all return the same results
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ScanBackend {
+    Scalar,
+    Sse2,
+    Avx2,
+}
+
+impl ScanBackend {
+    pub const ALL: [ScanBackend; 3] = [ScanBackend::Scalar, ScanBackend::Sse2, ScanBackend::Avx2];
+
+    // Whether the CPU running the program supports the backend
+    pub fn is_supported(self) -> bool {
+        match self {
+            ScanBackend::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            ScanBackend::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "x86_64")]
+            ScanBackend::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(not(target_arch = "x86_64"))]
+            ScanBackend::Sse2 | ScanBackend::Avx2 => false,
+        }
+    }
+
+    // The fastest backend the CPU supports, detected once
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<ScanBackend> = OnceLock::new();
+
+        *DETECTED.get_or_init(|| {
+            [ScanBackend::Avx2, ScanBackend::Sse2]
+                .iter()
+                .copied()
+                .find(|backend| backend.is_supported())
+                .unwrap_or(ScanBackend::Scalar)
+        })
+    }
+
+    // The index of the first byte from `start` that isn't an ASCII digit, or the length
+    pub fn digit_run_end(self, bytes: &[u8], start: usize) -> usize {
+        self.scan(bytes, start, Run::Digits)
+    }
+
+    // The index of the first byte from `start` that isn't a space, or the length
+    pub fn next_non_space(self, bytes: &[u8], start: usize) -> usize {
+        self.scan(bytes, start, Run::Spaces)
+    }
+
+    fn scan(self, bytes: &[u8], start: usize, run: Run) -> usize {
+        assert!(self.is_supported(), "{:?} is not supported", self);
+
+        match self {
+            ScanBackend::Scalar => scalar::scan(bytes, start, run),
+            // Safety: the CPU supports the instructions, as checked above
+            #[cfg(target_arch = "x86_64")]
+            ScanBackend::Sse2 => unsafe { x86_64::scan_sse2(bytes, start, run) },
+            #[cfg(target_arch = "x86_64")]
+            ScanBackend::Avx2 => unsafe { x86_64::scan_avx2(bytes, start, run) },
+            #[cfg(not(target_arch = "x86_64"))]
+            ScanBackend::Sse2 | ScanBackend::Avx2 => unreachable!(),
+        }
+    }
+}
+
+// The bytes of a run
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Run {
+    Digits,
+    Spaces,
+}
+
+impl Run {
+    fn contains(self, byte: u8) -> bool {
+        match self {
+            Run::Digits => byte.is_ascii_digit(),
+            Run::Spaces => byte == b' ',
+        }
+    }
+}
+
+// The index of the first byte from `start` that isn't an ASCII digit, with the detected backend
+pub fn digit_run_end(bytes: &[u8], start: usize) -> usize {
+    ScanBackend::detect().digit_run_end(bytes, start)
+}
+
+// The index of the first byte from `start` that isn't a space, with the detected backend
+pub fn next_non_space(bytes: &[u8], start: usize) -> usize {
+    ScanBackend::detect().next_non_space(bytes, start)
+}
+
+mod scalar {
+    use super::Run;
+
+    pub(super) fn scan(bytes: &[u8], start: usize, run: Run) -> usize {
+        bytes[start..]
+            .iter()
+            .position(|&byte| !run.contains(byte))
+            .map_or(bytes.len(), |i| start + i)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use std::arch::x86_64::*;
+
+    use super::{scalar, Run};
+
+    // Compare 16 bytes at a time, then the rest one at a time.
+    // Bytes of the run are set in the mask of every block, so the run ends at the first unset
+    // one. Signed comparisons work for digits, as bytes above 0x7f are negative.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn scan_sse2(bytes: &[u8], start: usize, run: Run) -> usize {
+        let mut i = start;
+
+        while i + 16 <= bytes.len() {
+            // Safety: the 16 bytes from `i` are in the slice
+            let block = unsafe { _mm_loadu_si128(bytes.as_ptr().add(i) as *const __m128i) };
+            let in_run = match run {
+                Run::Digits => _mm_and_si128(
+                    _mm_cmpgt_epi8(block, _mm_set1_epi8(b'0' as i8 - 1)),
+                    _mm_cmplt_epi8(block, _mm_set1_epi8(b'9' as i8 + 1)),
+                ),
+                Run::Spaces => _mm_cmpeq_epi8(block, _mm_set1_epi8(b' ' as i8)),
+            };
+
+            let mask = _mm_movemask_epi8(in_run) as u32;
+            if mask != 0xffff {
+                return i + (!mask).trailing_zeros() as usize;
+            }
+            i += 16;
+        }
+
+        scalar::scan(bytes, i, run)
+    }
+
+    // The same with 32 bytes at a time
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn scan_avx2(bytes: &[u8], start: usize, run: Run) -> usize {
+        let mut i = start;
+
+        while i + 32 <= bytes.len() {
+            // Safety: the 32 bytes from `i` are in the slice
+            let block = unsafe { _mm256_loadu_si256(bytes.as_ptr().add(i) as *const __m256i) };
+            let in_run = match run {
+                Run::Digits => _mm256_and_si256(
+                    _mm256_cmpgt_epi8(block, _mm256_set1_epi8(b'0' as i8 - 1)),
+                    _mm256_cmpgt_epi8(_mm256_set1_epi8(b'9' as i8 + 1), block),
+                ),
+                Run::Spaces => _mm256_cmpeq_epi8(block, _mm256_set1_epi8(b' ' as i8)),
+            };
+
+            let mask = _mm256_movemask_epi8(in_run) as u32;
+            if mask != u32::MAX {
+                return i + (!mask).trailing_zeros() as usize;
+            }
+            i += 32;
+        }
+
+        // Safety: AVX2 implies SSE2
+        unsafe { scan_sse2(bytes, i, run) }
+    }
+}
@@ -0,0 +1,187 @@
+use rand::Rng;
+
+use crate::differential::{first_disagreement, random_input};
+use crate::scan::ScanBackend;
+use crate::seeded;
+use crate::test_helpers::ALPHABET;
+use crate::tokenize::{
+    tokenize_with, Backend, ExpressionLexingError, Lexer, LexingError, OperatorKind, Token,
+};
+
+/// Bytes around the digits and the space, and bytes above 0x7f which are negative when signed
+const BYTES: [u8; 10] = [b'0', b'5', b'9', b' ', b'/', b':', b'\n', 0x80, 0xb0, 0xff];
+
+/// More digits and spaces on top of the shared alphabet, so that long runs are more likely
+const RUNS: [char; 6] = ['2', '5', '9', '9', ' ', ' '];
+
+fn supported_backends() -> Vec<ScanBackend> {
+    ScanBackend::ALL
+        .iter()
+        .copied()
+        .filter(|backend| backend.is_supported())
+        .collect()
+}
+
+/// Lex the string one character at a time, as `tokenize` did before bulk scanning
+fn tokenize_by_char(string: &str) -> Result<Vec<Token>, LexingError> {
+    let mut lexer = Lexer::new();
+    let mut tokens = vec![];
+
+    for c in string.chars() {
+        tokens.append(&mut lexer.feed(Some(c))?);
+    }
+    tokens.append(&mut lexer.feed(None)?);
+
+    Ok(tokens)
+}
+
+#[test]
+fn test_detected_backend_is_supported() {
+    assert!(ScanBackend::detect().is_supported());
+    assert!(ScanBackend::Scalar.is_supported());
+}
+
+#[test]
+fn test_scan_examples() {
+    let bytes = b"12345678901234567890123456789012345678 +  0.5";
+
+    for backend in supported_backends() {
+        assert_eq!(backend.digit_run_end(bytes, 0), 38);
+        assert_eq!(backend.digit_run_end(bytes, 38), 38);
+        assert_eq!(backend.next_non_space(bytes, 38), 39);
+        assert_eq!(backend.next_non_space(bytes, 40), 42);
+        assert_eq!(backend.digit_run_end(bytes, 44), bytes.len());
+        assert_eq!(backend.next_non_space(bytes, bytes.len()), bytes.len());
+    }
+}
+
+/// Bytes with a long run, ending anywhere in a block or in the remainder, and a start
+fn random_bytes() -> (Vec<u8>, usize) {
+    let mut rng = seeded::thread_rng();
+
+    let len = rng.gen_range(0..100);
+    let run = rng.gen_range(0..=len);
+    let (fill, other) = if rng.gen_bool(0.5) {
+        (b'7', b' ')
+    } else {
+        (b' ', b'7')
+    };
+    let mut bytes: Vec<u8> = (0..len)
+        .map(|i| {
+            if i < run {
+                fill
+            } else {
+                BYTES[rng.gen_range(0..BYTES.len())]
+            }
+        })
+        .collect();
+    if run < len && rng.gen_bool(0.5) {
+        bytes[run] = other;
+    }
+
+    (bytes, rng.gen_range(0..=len))
+}
+
+#[test]
+fn test_scan_backends_agree() {
+    let backends = supported_backends();
+
+    seeded::check(5000, random_bytes, |(bytes, start)| {
+        // Shrinking can leave the start past the end
+        let start = (*start).min(bytes.len());
+
+        let digits = ScanBackend::Scalar.digit_run_end(bytes, start);
+        let spaces = ScanBackend::Scalar.next_non_space(bytes, start);
+        for backend in backends.iter() {
+            assert_eq!(
+                (
+                    backend.digit_run_end(bytes, start),
+                    backend.next_non_space(bytes, start)
+                ),
+                (digits, spaces),
+                "\n backend: {:?}\n bytes: {:?}\n start: {}\n\n",
+                backend,
+                bytes,
+                start
+            );
+        }
+    });
+}
+
+#[test]
+fn test_bulk_feeding_agrees_with_feeding_characters() {
+    let mut hand_written = Lexer::with_backend(Backend::HandWritten);
+    let mut table_driven = Lexer::with_backend(Backend::TableDriven);
+
+    let alphabet: Vec<char> = ALPHABET.iter().chain(RUNS.iter()).copied().collect();
+
+    seeded::check(
+        5000,
+        || random_input(&alphabet, 80),
+        |input| {
+            for lexer in [&mut hand_written, &mut table_driven] {
+                let disagreement =
+                    first_disagreement(&mut tokenize_by_char, lexer, Some(input.to_string()));
+                assert_eq!(disagreement, None);
+            }
+        },
+    );
+}
+
+#[test]
+fn test_run_ends_before_multibyte_character_across_blocks() {
+    // 'é' takes the last byte of a 16 or 32 byte block and the first byte of the next one
+    for len in [15, 31, 47, 63] {
+        for (fill, prefix) in [('7', "1"), (' ', "")] {
+            let run: String = std::iter::repeat_n(fill, len - prefix.len()).collect();
+            let input = format!("{}{}é 2", prefix, run);
+            let bytes = input.as_bytes();
+
+            // Runs end at ASCII bytes, so at a character boundary
+            for backend in supported_backends() {
+                let end = if fill == ' ' {
+                    backend.next_non_space(bytes, 0)
+                } else {
+                    backend.digit_run_end(bytes, 0)
+                };
+                assert_eq!(
+                    end, len,
+                    "\n backend: {:?}\n input: {:?}\n\n",
+                    backend, input
+                );
+                assert!(input.is_char_boundary(end));
+            }
+
+            for backend in [Backend::HandWritten, Backend::TableDriven] {
+                assert_eq!(
+                    tokenize_with(&mut Lexer::with_backend(backend), &input),
+                    Err(LexingError::IncorrectExpression(
+                        ExpressionLexingError::UnexpectedCharacter('é')
+                    )),
+                    "\n backend: {:?}\n input: {:?}\n\n",
+                    backend,
+                    input
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bulk_feeding_line_oriented() {
+    let mut lexer = Lexer::line_oriented();
+
+    assert_eq!(
+        lexer.feed_str("  12345678901234567890   +   3\n   4"),
+        Ok(vec![
+            Token::Number(12345678901234567890.0),
+            Token::Operator(OperatorKind::Add),
+            Token::Number(3.0),
+            Token::EndOfExpression,
+        ])
+    );
+    assert_eq!(
+        lexer.feed(None),
+        Ok(vec![Token::Number(4.0), Token::EndOfExpression])
+    );
+}
@@ -0,0 +1,431 @@
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::shrink::shrink;
+
+/// The variable to set to replay a seed, e.g. `PASTA_TEST_SEED=1234 cargo test`
+pub const SEED_VARIABLE: &str = "PASTA_TEST_SEED";
+
+/// The variable to set to save failing inputs to another corpus, e.g.
+/// `PASTA_TEST_CORPUS=regressions/inputs.txt cargo test` to save them to the checked-in one
+pub const CORPUS_VARIABLE: &str = "PASTA_TEST_CORPUS";
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::seed_from_u64(0));
+}
+
+/// A handle to the seeded generator of the current thread, replacing `rand::thread_rng`
+pub struct TestRng;
+
+pub fn thread_rng() -> TestRng {
+    TestRng
+}
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+pub fn reseed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// The seed from `PASTA_TEST_SEED`, or a random one
+fn base_seed() -> u64 {
+    match std::env::var(SEED_VARIABLE) {
+        Ok(seed) => seed
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("{} should be a number, got {:?}", SEED_VARIABLE, seed)),
+        Err(_) => rand::random(),
+    }
+}
+
+/// The name libtest gives to the thread running a test
+fn test_name() -> String {
+    std::thread::current()
+        .name()
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+// ====================
+// Inputs
+// ====================
+
+/// A generated input, saved on one line of a corpus and shrunk when it makes a test fail
+pub trait Input: Clone {
+    /// The input on one line
+    fn encode(&self) -> String;
+
+    /// Read back an input encoded at the start of the string, and skip it
+    fn decode(encoded: &mut &str) -> Option<Self>;
+
+    /// The smallest input found for which the test still fails
+    fn shrink(&self, fails: &mut dyn FnMut(&Self) -> bool) -> Self;
+}
+
+/// Skip the expected text at the start of the string, after any spaces
+fn skip(encoded: &mut &str, expected: &str) -> Option<()> {
+    *encoded = encoded.trim_start().strip_prefix(expected)?;
+    Some(())
+}
+
+impl Input for String {
+    fn encode(&self) -> String {
+        quote(self)
+    }
+
+    fn decode(encoded: &mut &str) -> Option<Self> {
+        let quoted = encoded.trim_start();
+
+        // The closing quote is the first one which isn't escaped
+        let mut escaped = false;
+        let (end, _) = quoted.char_indices().skip(1).find(|&(_, c)| {
+            let closing = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            closing
+        })?;
+
+        *encoded = &quoted[end + 1..];
+        unquote(&quoted[..=end])
+    }
+
+    fn shrink(&self, fails: &mut dyn FnMut(&Self) -> bool) -> Self {
+        shrink(self, |input| fails(&input.to_string()))
+    }
+}
+
+macro_rules! unsigned_input {
+    ($($type:ty),*) => {
+        $(
+            impl Input for $type {
+                fn encode(&self) -> String {
+                    self.to_string()
+                }
+
+                fn decode(encoded: &mut &str) -> Option<Self> {
+                    let digits = encoded.trim_start();
+                    let end = digits
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(digits.len());
+
+                    *encoded = &digits[end..];
+                    digits[..end].parse().ok()
+                }
+
+                /// Towards zero, halving first
+                fn shrink(&self, fails: &mut dyn FnMut(&Self) -> bool) -> Self {
+                    let mut value = *self;
+                    while let Some(smaller) = [0, value / 2, value.saturating_sub(1)]
+                        .iter()
+                        .copied()
+                        .find(|&smaller| smaller < value && fails(&smaller))
+                    {
+                        value = smaller;
+                    }
+                    value
+                }
+            }
+        )*
+    };
+}
+
+unsigned_input!(u8, usize);
+
+impl<T: Input> Input for Vec<T> {
+    fn encode(&self) -> String {
+        let items: Vec<String> = self.iter().map(Input::encode).collect();
+        format!("[{}]", items.join(", "))
+    }
+
+    fn decode(encoded: &mut &str) -> Option<Self> {
+        skip(encoded, "[")?;
+        let mut items = vec![];
+        if skip(encoded, "]").is_some() {
+            return Some(items);
+        }
+
+        loop {
+            items.push(T::decode(encoded)?);
+            if skip(encoded, "]").is_some() {
+                return Some(items);
+            }
+            skip(encoded, ",")?;
+        }
+    }
+
+    /// Without runs of items, from long ones down to single items, then with smaller items
+    fn shrink(&self, fails: &mut dyn FnMut(&Self) -> bool) -> Self {
+        let mut items = self.clone();
+
+        let mut count = items.len();
+        while count > 0 {
+            let mut start = 0;
+            while start + count <= items.len() {
+                let mut candidate = items.clone();
+                candidate.drain(start..start + count);
+                if fails(&candidate) {
+                    items = candidate;
+                } else {
+                    start += 1;
+                }
+            }
+            count /= 2;
+        }
+
+        for i in 0..items.len() {
+            let item = items[i].shrink(&mut |item| {
+                let mut candidate = items.clone();
+                candidate[i] = item.clone();
+                fails(&candidate)
+            });
+            items[i] = item;
+        }
+
+        items
+    }
+}
+
+macro_rules! tuple_input {
+    ($first_type:ident $first:tt $(, $type:ident $index:tt)*) => {
+        impl<$first_type: Input $(, $type: Input)*> Input for ($first_type, $($type),*) {
+            fn encode(&self) -> String {
+                let items = [self.$first.encode() $(, self.$index.encode())*];
+                format!("({})", items.join(", "))
+            }
+
+            fn decode(encoded: &mut &str) -> Option<Self> {
+                skip(encoded, "(")?;
+                let input = (
+                    $first_type::decode(encoded)?,
+                    $({
+                        skip(encoded, ",")?;
+                        $type::decode(encoded)?
+                    }),*
+                );
+                skip(encoded, ")")?;
+                Some(input)
+            }
+
+            /// One item at a time, until none of them gets smaller
+            fn shrink(&self, fails: &mut dyn FnMut(&Self) -> bool) -> Self {
+                let mut input = self.clone();
+
+                loop {
+                    let before = input.encode();
+                    let item = input.$first.shrink(&mut |item| {
+                        let mut candidate = input.clone();
+                        candidate.$first = item.clone();
+                        fails(&candidate)
+                    });
+                    input.$first = item;
+                    $(
+                        let item = input.$index.shrink(&mut |item| {
+                            let mut candidate = input.clone();
+                            candidate.$index = item.clone();
+                            fails(&candidate)
+                        });
+                        input.$index = item;
+                    )*
+                    if input.encode() == before {
+                        return input;
+                    }
+                }
+            }
+        }
+    };
+}
+
+tuple_input!(A 0, B 1);
+tuple_input!(A 0, B 1, C 2);
+tuple_input!(A 0, B 1, C 2, D 3);
+
+// ====================
+// Regression corpus
+// ====================
+
+/// The corpus checked in the repository, of failing inputs worth keeping
+pub fn checked_in_corpus() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("regressions/inputs.txt")
+}
+
+/// Where failing inputs are saved: the corpus from `PASTA_TEST_CORPUS`, or a gitignored one
+pub fn corpus_path() -> PathBuf {
+    match std::env::var_os(CORPUS_VARIABLE) {
+        Some(path) => PathBuf::from(path),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("regressions/local.txt"),
+    }
+}
+
+/// Quote the input on one line, escaped like a Rust string
+pub fn quote(input: &str) -> String {
+    format!("{:?}", input)
+}
+
+/// Read back an input quoted by `quote`
+pub fn unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut input = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            input.push(c);
+            continue;
+        }
+
+        let c = match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'u' => {
+                let code: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                char::from_u32(u32::from_str_radix(code.strip_prefix('{')?, 16).ok()?)?
+            }
+            _ => return None,
+        };
+        input.push(c);
+    }
+
+    Some(input)
+}
+
+/// The inputs which made the test fail before
+pub fn corpus_inputs<T: Input>(corpus: &Path, name: &str) -> Vec<T> {
+    let content = fs::read_to_string(corpus).unwrap_or_default();
+
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (test, mut encoded) = line.split_once(' ')?;
+            if test != name {
+                return None;
+            }
+            let input = T::decode(&mut encoded)?;
+            if encoded.trim().is_empty() {
+                Some(input)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn persist<T: Input>(corpus: &Path, name: &str, input: &T) {
+    let encoded = input.encode();
+    if corpus_inputs::<T>(corpus, name)
+        .iter()
+        .any(|saved| saved.encode() == encoded)
+    {
+        return;
+    }
+    if let Some(parent) = corpus.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(corpus);
+    match file.and_then(|mut file| writeln!(file, "{} {}", name, encoded)) {
+        Ok(()) => eprintln!("{}: input saved to {}", name, corpus.display()),
+        Err(error) => eprintln!("{}: could not save the input: {}", name, error),
+    }
+}
+
+// ====================
+// Property checks
+// ====================
+
+/// Whether the test panics for the input, with the generator seeded like the failing run
+fn fails<T>(test: &mut impl FnMut(&T), seed: u64, input: &T) -> bool {
+    reseed(seed);
+    panic::catch_unwind(AssertUnwindSafe(|| test(input))).is_err()
+}
+
+/// Check a property which must hold for any input, on the inputs which made it fail before and
+/// then on `count` generated ones. A failing input is shrunk, and the smallest one still failing
+/// is saved to `corpus_path()`.
+pub fn check<T: Input>(count: u32, generate: impl FnMut() -> T, test: impl FnMut(&T)) {
+    let saved = corpus_path();
+    let checked_in = checked_in_corpus();
+    let corpora = if saved == checked_in {
+        vec![checked_in]
+    } else {
+        vec![checked_in, saved.clone()]
+    };
+
+    check_with_corpora(&corpora, &saved, count, generate, test);
+}
+
+pub fn check_with_corpora<T: Input>(
+    corpora: &[PathBuf],
+    saved: &Path,
+    count: u32,
+    mut generate: impl FnMut() -> T,
+    mut test: impl FnMut(&T),
+) {
+    let name = test_name();
+
+    for corpus in corpora.iter() {
+        for input in corpus_inputs::<T>(corpus, &name) {
+            reseed(0);
+            if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(|| test(&input))) {
+                eprintln!(
+                    "{}: failed with input {} from {}",
+                    name,
+                    input.encode(),
+                    corpus.display()
+                );
+                panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    let base = base_seed();
+    println!("{}: seed {}", name, base);
+
+    for i in 0..count {
+        let seed = base.wrapping_add(i.into());
+        reseed(seed);
+        let input = generate();
+
+        reseed(seed);
+        if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(|| test(&input))) {
+            let minimal = input.shrink(&mut |input| fails(&mut test, seed, input));
+            eprintln!(
+                "{}: failed with seed {}, replay with {}={}\n input: {}\n minimal input: {}",
+                name,
+                seed,
+                SEED_VARIABLE,
+                seed,
+                input.encode(),
+                minimal.encode()
+            );
+            persist(saved, &name, &minimal);
+            panic::resume_unwind(panic);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+
+use rand::Rng;
+
+use crate::seeded::{
+    check_with_corpora, checked_in_corpus, corpus_inputs, corpus_path, quote, reseed, thread_rng,
+    unquote, Input,
+};
+
+fn temporary_corpus(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pasta-tcs-{}-{}.txt", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn current_test_name() -> String {
+    std::thread::current().name().unwrap().to_string()
+}
+
+/// A random string of digits and spaces
+fn random_input() -> String {
+    let mut rng = thread_rng();
+    (0..rng.gen_range(0..20))
+        .map(|_| if rng.gen_bool(0.8) { '1' } else { ' ' })
+        .collect()
+}
+
+#[test]
+fn test_same_seed_same_values() {
+    reseed(7);
+    let first: Vec<u32> = (0..10).map(|_| thread_rng().gen()).collect();
+    reseed(7);
+    let second: Vec<u32> = (0..10).map(|_| thread_rng().gen()).collect();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_shrunk_failing_input_is_saved_and_replayed_first() {
+    let corpus = temporary_corpus("failing");
+    let name = current_test_name();
+
+    // Fails for inputs with a space
+    let result = panic::catch_unwind(|| {
+        check_with_corpora(
+            std::slice::from_ref(&corpus),
+            &corpus,
+            1000,
+            random_input,
+            |input| {
+                assert!(!input.contains(' '));
+            },
+        )
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        corpus_inputs::<String>(&corpus, &name),
+        vec![" ".to_string()]
+    );
+
+    // The saved input is checked before any new one is generated
+    let mut inputs = vec![];
+    let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        check_with_corpora(
+            std::slice::from_ref(&corpus),
+            &corpus,
+            1,
+            random_input,
+            |input| {
+                inputs.push(input.to_string());
+            },
+        )
+    }));
+    assert_eq!(inputs.first(), Some(&" ".to_string()));
+
+    fs::remove_file(&corpus).unwrap();
+}
+
+#[test]
+fn test_passing_check_saves_nothing() {
+    let corpus = temporary_corpus("passing");
+    let mut runs = 0;
+
+    check_with_corpora(
+        std::slice::from_ref(&corpus),
+        &corpus,
+        10,
+        random_input,
+        |_| runs += 1,
+    );
+
+    assert_eq!(runs, 10);
+    assert!(!corpus.exists());
+}
+
+#[test]
+fn test_corpus_format() {
+    let corpus = temporary_corpus("format");
+    fs::write(
+        &corpus,
+        "# a comment\nsome::test \"1 +\"\nother::test \"2\"\nsome::test \"\\n\\\"x\\u{301}\\\"\"\nsome::test nope\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        corpus_inputs::<String>(&corpus, "some::test"),
+        vec!["1 +".to_string(), "\n\"x\u{301}\"".to_string()]
+    );
+    assert_eq!(
+        corpus_inputs::<String>(&corpus, "missing::test"),
+        Vec::<String>::new()
+    );
+
+    fs::remove_file(&corpus).unwrap();
+}
+
+#[test]
+fn test_quote_round_trip() {
+    for input in ["", "1 + 2", "\n\r\t\0", "\\\"'", "é\u{301}\u{7f}x"] {
+        let quoted = quote(input);
+        assert!(!quoted.contains('\n'));
+        assert_eq!(unquote(&quoted), Some(input.to_string()), "{}", quoted);
+    }
+
+    assert_eq!(unquote("1 +"), None);
+    assert_eq!(unquote("\"\\q\""), None);
+}
+
+#[test]
+fn test_failures_are_not_saved_to_the_checked_in_corpus_by_default() {
+    if std::env::var_os(crate::seeded::CORPUS_VARIABLE).is_none() {
+        assert_ne!(corpus_path(), checked_in_corpus());
+    }
+}
+
+#[test]
+fn test_encode_round_trip() {
+    fn round_trip<T: Input + PartialEq + std::fmt::Debug>(input: T) {
+        let encoded = input.encode();
+        assert!(!encoded.contains('\n'));
+
+        let mut rest = encoded.as_str();
+        assert_eq!(T::decode(&mut rest), Some(input), "{}", encoded);
+        assert_eq!(rest, "");
+    }
+
+    round_trip("1 + \"2\", \\".to_string());
+    round_trip(vec![0u8, 0x80, 255]);
+    round_trip(Vec::<u8>::new());
+    round_trip(("a, b)".to_string(), 7usize));
+    round_trip((vec!["[".to_string()], 0usize, 1u8, "".to_string()));
+
+    assert_eq!(usize::decode(&mut "x"), None);
+    assert_eq!(Vec::<u8>::decode(&mut "[1, 2"), None);
+    assert_eq!(<(usize, usize)>::decode(&mut "(1 2)"), None);
+}
+
+#[test]
+fn test_composite_inputs_shrink() {
+    let bytes = vec![5u8, 200, 7, 255].shrink(&mut |bytes| bytes.iter().any(|&b| b > 100));
+    assert_eq!(bytes, vec![101]);
+
+    let input = ("1 + x * 2".to_string(), 40usize);
+    let minimal = input.shrink(&mut |(text, n)| text.contains('x') && *n >= 3);
+    assert_eq!(minimal, ("x".to_string(), 3));
+}
+
+#[test]
+fn test_composite_input_is_saved() {
+    let corpus = temporary_corpus("composite");
+    let name = current_test_name();
+
+    let result = panic::catch_unwind(|| {
+        check_with_corpora(
+            std::slice::from_ref(&corpus),
+            &corpus,
+            100,
+            || (random_input(), thread_rng().gen_range(0..100usize)),
+            |(input, n)| {
+                assert!(input.len() < *n);
+            },
+        )
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        corpus_inputs::<(String, usize)>(&corpus, &name),
+        vec![(String::new(), 0)]
+    );
+
+    fs::remove_file(&corpus).unwrap();
+}
@@ -0,0 +1,145 @@
+// ====================
+// Lexemes
+// ====================
+
+// A run of characters shrunk as a whole
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum LexemeKind {
+    Whitespace,
+    // Digits and points
+    Number,
+    // Any other single character
+    Other,
+}
+
+fn lexeme_kind(c: char) -> LexemeKind {
+    match c {
+        ' ' => LexemeKind::Whitespace,
+        '0'..='9' | '.' => LexemeKind::Number,
+        _ => LexemeKind::Other,
+    }
+}
+
+fn split_lexemes(input: &str) -> Vec<(LexemeKind, String)> {
+    let mut lexemes: Vec<(LexemeKind, String)> = vec![];
+
+    for c in input.chars() {
+        let kind = lexeme_kind(c);
+        match lexemes.last_mut() {
+            Some((last, string)) if *last == kind && kind != LexemeKind::Other => string.push(c),
+            _ => lexemes.push((kind, c.to_string())),
+        }
+    }
+
+    lexemes
+}
+
+fn join(lexemes: &[(LexemeKind, String)]) -> String {
+    lexemes.iter().map(|(_, string)| string.as_str()).collect()
+}
+
+// ====================
+// Candidates
+// ====================
+
+// Every input with `count` consecutive lexemes removed
+fn removed_lexemes(lexemes: &[(LexemeKind, String)], count: usize) -> Vec<String> {
+    (0..=lexemes.len().saturating_sub(count))
+        .map(|start| {
+            let mut lexemes = lexemes.to_vec();
+            lexemes.drain(start..start + count);
+            join(&lexemes)
+        })
+        .collect()
+}
+
+// Every input with one lexeme replaced by a simpler one
+fn simplified_lexemes(lexemes: &[(LexemeKind, String)]) -> Vec<String> {
+    let mut candidates = vec![];
+
+    for (i, (kind, string)) in lexemes.iter().enumerate() {
+        let replacements: Vec<String> = match kind {
+            LexemeKind::Whitespace => vec![" ".to_string()],
+            LexemeKind::Number => {
+                // Drop one character at a time, then replace the whole number
+                let chars: Vec<char> = string.chars().collect();
+                let mut replacements: Vec<String> = (0..chars.len())
+                    .map(|skip| {
+                        chars
+                            .iter()
+                            .enumerate()
+                            .filter(|&(j, _)| j != skip)
+                            .map(|(_, c)| c)
+                            .collect()
+                    })
+                    .collect();
+                replacements.extend(vec!["0".to_string(), "1".to_string()]);
+                replacements
+            }
+            LexemeKind::Other => vec![],
+        };
+
+        for replacement in replacements {
+            let mut lexemes = lexemes.to_vec();
+            lexemes[i].1 = replacement;
+            candidates.push(join(&lexemes));
+        }
+    }
+
+    candidates
+}
+
+// Every input with one character removed, or replaced by '0'
+fn simplified_characters(input: &str) -> Vec<String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut candidates = vec![];
+
+    for i in 0..chars.len() {
+        let before: String = chars[..i].iter().collect();
+        let after: String = chars[i + 1..].iter().collect();
+        candidates.push(format!("{}{}", before, after));
+        candidates.push(format!("{}0{}", before, after));
+    }
+
+    candidates
+}
+
+// Shorter inputs are simpler, and equally long ones are ordered by their characters
+fn is_simpler(candidate: &str, input: &str) -> bool {
+    let (candidate_len, input_len) = (candidate.chars().count(), input.chars().count());
+    candidate_len < input_len || (candidate_len == input_len && candidate < input)
+}
+
+// ====================
+// Shrinking
+// ====================
+
+// Minimize an input for which `fails` returns true, keeping it failing.
+// Lexemes are removed, then numbers and whitespace simplified, then single characters,
+// until none of these makes the input simpler. An input which doesn't fail is returned as is.
+pub fn shrink(input: &str, mut fails: impl FnMut(&str) -> bool) -> String {
+    let mut input = input.to_string();
+    if !fails(&input) {
+        return input;
+    }
+
+    loop {
+        let lexemes = split_lexemes(&input);
+
+        let mut candidates = vec![];
+        for count in (1..=lexemes.len()).rev() {
+            candidates.append(&mut removed_lexemes(&lexemes, count));
+        }
+        candidates.append(&mut simplified_lexemes(&lexemes));
+        candidates.append(&mut simplified_characters(&input));
+
+        let simpler = candidates
+            .into_iter()
+            .find(|candidate| is_simpler(candidate, &input) && fails(candidate));
+
+        match simpler {
+            Some(simpler) => input = simpler,
+            None => return input,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::shrink::shrink;
+use crate::tokenize::{tokenize, ExpressionLexingError, LexingError, NumberLexingError};
+
+fn fails_with(error: LexingError) -> impl Fn(&str) -> bool {
+    move |input| tokenize(input) == Err(error)
+}
+
+#[test]
+fn test_shrink_removes_tokens() {
+    let minimal = shrink(
+        "12 + 0.5 *  7 / 0.25 - 3 4 + 99",
+        fails_with(LexingError::IncorrectExpression(
+            ExpressionLexingError::ExpectedOperator,
+        )),
+    );
+
+    assert_eq!(minimal, "0 0");
+}
+
+#[test]
+fn test_shrink_shortens_numbers() {
+    let minimal = shrink(
+        "   987 +  0.123456 * 4210.5  ",
+        fails_with(LexingError::IncorrectNumber(
+            NumberLexingError::NonZeroIntegerBeforePoint,
+        )),
+    );
+
+    assert_eq!(minimal, "1.");
+}
+
+#[test]
+fn test_shrink_collapses_whitespace() {
+    // Needs a space, and a multi-byte character
+    let minimal = shrink("1    +     2    é", |input| {
+        input.contains(' ') && input.contains('é')
+    });
+
+    assert_eq!(minimal, " é");
+}
+
+#[test]
+fn test_shrink_keeps_passing_input() {
+    assert_eq!(shrink("1 + 2", |_| false), "1 + 2");
+}
+
+#[test]
+fn test_shrink_panicking_predicate() {
+    // A predicate over a bug which panics, like a failing assertion in an evaluator
+    let panics = |input: &str| {
+        let input = input.to_string();
+        std::panic::catch_unwind(move || {
+            let tokens = tokenize(&input).unwrap_or_default();
+            assert!(tokens.len() < 5);
+        })
+        .is_err()
+    };
+
+    let minimal = shrink("10 / 20 / 30 / 40 / 50 / 60", panics);
+
+    assert_eq!(minimal, "0/0/0");
+}
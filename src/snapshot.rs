@@ -0,0 +1,86 @@
+use crate::tokenize::{Backend, Lexer, NumberRepresentation, State};
+
+// The snapshot format version, bumped whenever the meaning of the fields changes
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+// ====================
+// Snapshot
+// ====================
+
+// A stable copy of a lexer's progress, which can be stored and resumed later
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LexerSnapshot {
+    pub version: u32,
+    pub state: State,
+    // The characters of the number being lexed
+    pub buffer: String,
+    pub line_oriented: bool,
+    // Whether nothing was fed since the start of the current line
+    pub line_start: bool,
+    pub backend: Backend,
+    pub numbers: NumberRepresentation,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SnapshotError {
+    UnsupportedVersion(u32),
+    BufferDoesNotMatchState(State),
+}
+
+// Check that the buffer could have been produced by feeding the lexer up to `state`
+fn is_valid_buffer(state: State, buffer: &str) -> bool {
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    match state {
+        State::Initial | State::WhitespaceBeforeOperator | State::End | State::Error => {
+            buffer.is_empty()
+        }
+        State::NumberZeroInteger => buffer == "0",
+        State::NumberPoint => buffer == "0.",
+        State::Number => match buffer.strip_prefix("0.") {
+            Some(decimals) => is_digits(decimals),
+            None => is_digits(buffer) && !buffer.starts_with('0'),
+        },
+    }
+}
+
+impl Lexer {
+    // Take a snapshot of the current lexer progress
+    pub fn snapshot(&self) -> LexerSnapshot {
+        // An error can leave the number being lexed in the buffer, which is never used again
+        let buffer = match self.state {
+            State::End | State::Error => String::new(),
+            _ => self.buffer.iter().collect(),
+        };
+
+        LexerSnapshot {
+            version: SNAPSHOT_VERSION,
+            state: self.state,
+            buffer,
+            line_oriented: self.line_oriented,
+            line_start: self.line_start,
+            backend: self.backend,
+            numbers: self.numbers,
+        }
+    }
+
+    // Recreate a lexer from a snapshot, continuing exactly where the original left off
+    pub fn from_snapshot(snapshot: &LexerSnapshot) -> Result<Self, SnapshotError> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot.version));
+        }
+        if !is_valid_buffer(snapshot.state, &snapshot.buffer) {
+            return Err(SnapshotError::BufferDoesNotMatchState(snapshot.state));
+        }
+
+        Ok(Self {
+            buffer: snapshot.buffer.chars().collect(),
+            state: snapshot.state,
+            line_oriented: snapshot.line_oriented,
+            line_start: snapshot.line_start,
+            backend: snapshot.backend,
+            numbers: snapshot.numbers,
+        })
+    }
+}
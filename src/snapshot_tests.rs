@@ -0,0 +1,209 @@
+use crate::decimal::Decimal;
+use crate::snapshot::{LexerSnapshot, SnapshotError, SNAPSHOT_VERSION};
+use crate::tokenize::{
+    tokenize, Backend, ExpressionLexingError, Lexer, LexingError, NumberLexingError,
+    NumberRepresentation, State, Token,
+};
+
+/// Feed the whole string to the lexer, collecting the tokens
+fn feed_str(lexer: &mut Lexer, string: &str) -> Result<Vec<Token>, LexingError> {
+    let mut tokens = vec![];
+    for c in string.chars() {
+        tokens.append(&mut lexer.feed(Some(c))?);
+    }
+    Ok(tokens)
+}
+
+fn feed_str_and_end(lexer: &mut Lexer, string: &str) -> Result<Vec<Token>, LexingError> {
+    let mut tokens = feed_str(lexer, string)?;
+    tokens.append(&mut lexer.feed(None)?);
+    Ok(tokens)
+}
+
+#[test]
+fn test_resume_at_every_split_point() {
+    let input = "0.25 + 13 *7 - 0.5";
+
+    for split in 0..=input.len() {
+        let (head, tail) = input.split_at(split);
+
+        let mut lexer = Lexer::new();
+        let mut tokens = feed_str(&mut lexer, head).unwrap();
+
+        let snapshot = lexer.snapshot();
+        let mut resumed = Lexer::from_snapshot(&snapshot).unwrap();
+        tokens.append(&mut feed_str_and_end(&mut resumed, tail).unwrap());
+
+        assert_eq!(Ok(tokens), tokenize(input), "\n split at: {}\n\n", split);
+    }
+}
+
+#[test]
+fn test_fork_alternative_continuations() {
+    let mut lexer = Lexer::new();
+    feed_str(&mut lexer, "3 + 0.").unwrap();
+    assert_eq!(lexer.state(), State::NumberPoint);
+
+    let mut valid = lexer.clone();
+    let mut invalid = lexer;
+
+    assert_eq!(
+        feed_str_and_end(&mut valid, "5"),
+        Ok(vec![Token::Number(0.5)])
+    );
+    assert_eq!(
+        feed_str_and_end(&mut invalid, " "),
+        Err(LexingError::IncorrectNumber(
+            NumberLexingError::ExpectedDigitAfterPoint
+        ))
+    );
+}
+
+#[test]
+fn test_snapshot_contents() {
+    let mut lexer = Lexer::new();
+    feed_str(&mut lexer, "1 - 0.07").unwrap();
+
+    assert_eq!(
+        lexer.snapshot(),
+        LexerSnapshot {
+            version: SNAPSHOT_VERSION,
+            state: State::Number,
+            buffer: "0.07".to_string(),
+            line_oriented: false,
+            line_start: false,
+            backend: Backend::HandWritten,
+            numbers: NumberRepresentation::Float,
+        }
+    );
+}
+
+#[test]
+fn test_snapshot_keeps_backend() {
+    let mut lexer = Lexer::with_backend(Backend::TableDriven);
+    feed_str(&mut lexer, "1 + 0.").unwrap();
+
+    let resumed = Lexer::from_snapshot(&lexer.snapshot()).unwrap();
+    assert_eq!(resumed.snapshot().backend, Backend::TableDriven);
+    assert_eq!(resumed.snapshot(), lexer.snapshot());
+}
+
+#[test]
+fn test_snapshot_keeps_numbers() {
+    let mut lexer = Lexer::new()
+        .backend(Backend::TableDriven)
+        .numbers(NumberRepresentation::Decimal)
+        .by_line(true);
+    feed_str(&mut lexer, "1 + 0.2").unwrap();
+
+    let mut resumed = Lexer::from_snapshot(&lexer.snapshot()).unwrap();
+    assert_eq!(resumed.snapshot(), lexer.snapshot());
+    assert_eq!(
+        feed_str_and_end(&mut resumed, "5"),
+        Ok(vec![
+            Token::Decimal("0.25".parse::<Decimal>().unwrap()),
+            Token::EndOfExpression
+        ])
+    );
+}
+
+#[test]
+fn test_snapshot_of_failed_lexer() {
+    let mut lexer = Lexer::new();
+    assert_eq!(
+        feed_str(&mut lexer, "1 2"),
+        Err(LexingError::IncorrectExpression(
+            ExpressionLexingError::ExpectedOperator
+        ))
+    );
+
+    let resumed = Lexer::from_snapshot(&lexer.snapshot()).unwrap();
+    assert_eq!(resumed.state(), State::Error);
+
+    // Failing in the middle of a number leaves it in the buffer
+    let mut lexer = Lexer::new();
+    assert_eq!(
+        feed_str_and_end(&mut lexer, "12."),
+        Err(LexingError::IncorrectNumber(
+            NumberLexingError::NonZeroIntegerBeforePoint
+        ))
+    );
+
+    let snapshot = lexer.snapshot();
+    assert_eq!(snapshot.buffer, "");
+    assert_eq!(
+        Lexer::from_snapshot(&snapshot).unwrap().state(),
+        State::Error
+    );
+}
+
+#[test]
+fn test_unsupported_version() {
+    let snapshot = LexerSnapshot {
+        version: SNAPSHOT_VERSION + 1,
+        state: State::Initial,
+        buffer: String::new(),
+        line_oriented: false,
+        line_start: true,
+        backend: Backend::HandWritten,
+        numbers: NumberRepresentation::Float,
+    };
+
+    assert_eq!(
+        Lexer::from_snapshot(&snapshot).err(),
+        Some(SnapshotError::UnsupportedVersion(SNAPSHOT_VERSION + 1))
+    );
+}
+
+#[test]
+fn test_buffer_does_not_match_state() {
+    let invalid = vec![
+        (State::Initial, "1"),
+        (State::NumberZeroInteger, "1"),
+        (State::NumberPoint, "0"),
+        (State::Number, "01"),
+        (State::Number, "1.5"),
+        (State::Number, "0."),
+        (State::Number, ""),
+        (State::WhitespaceBeforeOperator, "0"),
+    ];
+
+    for (state, buffer) in invalid.into_iter() {
+        let snapshot = LexerSnapshot {
+            version: SNAPSHOT_VERSION,
+            state,
+            buffer: buffer.to_string(),
+            line_oriented: false,
+            line_start: false,
+            backend: Backend::HandWritten,
+            numbers: NumberRepresentation::Float,
+        };
+
+        assert_eq!(
+            Lexer::from_snapshot(&snapshot).err(),
+            Some(SnapshotError::BufferDoesNotMatchState(state)),
+            "\n buffer: \"{}\"\n\n",
+            buffer
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let mut lexer = Lexer::new();
+    feed_str(&mut lexer, "0.5 * 12").unwrap();
+
+    let json = serde_json::to_string(&lexer.snapshot()).unwrap();
+    assert_eq!(
+        json,
+        r#"{"version":2,"state":"Number","buffer":"12","line_oriented":false,"line_start":false,"backend":"HandWritten","numbers":"Float"}"#
+    );
+
+    let snapshot: LexerSnapshot = serde_json::from_str(&json).unwrap();
+    let mut resumed = Lexer::from_snapshot(&snapshot).unwrap();
+    assert_eq!(
+        feed_str_and_end(&mut resumed, "3"),
+        Ok(vec![Token::Number(123.0)])
+    );
+}
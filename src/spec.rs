@@ -0,0 +1,482 @@
+// Declarative lexer specifications, see `src/grammar.lex` for the format.
+// A specification describes the states, character classes and transitions of a lexer, so grammar
+// variants such as other whitespace or number formats need no code. The tokens and errors are
+// fixed: transitions emit `number` and `operator` tokens and raise the errors of `ErrorName`, so
+// a new kind of token still needs code changes.
+// This module doesn't use the rest of the crate, because build.rs includes it to generate the default lexer.
+
+// ====================
+// Compiled specification
+// ====================
+
+// The errors a specification can name, mirroring the lexing errors
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ErrorName {
+    ExpectedDigitAfterPoint,
+    NonZeroIntegerBeforePoint,
+    MissingIntegerBeforePoint,
+    ExpectedPointAfterZero,
+    UnexpectedCharacter,
+    ExpectedNumber,
+    ExpectedOperator,
+}
+
+impl ErrorName {
+    pub const ALL: [ErrorName; 7] = [
+        ErrorName::ExpectedDigitAfterPoint,
+        ErrorName::NonZeroIntegerBeforePoint,
+        ErrorName::MissingIntegerBeforePoint,
+        ErrorName::ExpectedPointAfterZero,
+        ErrorName::UnexpectedCharacter,
+        ErrorName::ExpectedNumber,
+        ErrorName::ExpectedOperator,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|error| format!("{:?}", error) == name)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Next {
+    State(usize),
+    End,
+    Error(ErrorName),
+}
+
+// The transition for a state and input class
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Cell {
+    pub next: Next,
+    // Whether the character is pushed to the number buffer
+    pub push: bool,
+    // When both are emitted, the number comes first
+    pub emit_number: bool,
+    pub emit_operator: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CompiledSpec {
+    pub state_names: Vec<String>,
+    pub start: usize,
+    // The declared classes, followed by `other` and `eoi`
+    pub class_names: Vec<String>,
+    // The class of every character of the declared classes, sorted by character
+    pub chars: Vec<(char, usize)>,
+    // Indexed by `state * class_names.len() + class`
+    pub cells: Vec<Cell>,
+}
+
+impl CompiledSpec {
+    pub fn other_class(&self) -> usize {
+        self.class_names.len() - 2
+    }
+
+    pub fn end_of_input_class(&self) -> usize {
+        self.class_names.len() - 1
+    }
+
+    // Get the class of a character `Some(char)`, or of `None` for end of string
+    pub fn class_of(&self, c: Option<char>) -> usize {
+        match c {
+            None => self.end_of_input_class(),
+            Some(c) => match self.chars.binary_search_by_key(&c, |&(c, _)| c) {
+                Ok(index) => self.chars[index].1,
+                Err(_) => self.other_class(),
+            },
+        }
+    }
+
+    pub fn cell(&self, state: usize, class: usize) -> Cell {
+        self.cells[state * self.class_names.len() + class]
+    }
+
+    // Generate the Rust source of a `compiled()` function returning this specification
+    pub fn to_rust(&self) -> String {
+        let strings = |strings: &[String]| {
+            strings
+                .iter()
+                .map(|s| format!("{:?}.to_string()", s))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut rust = String::new();
+        rust.push_str("pub fn compiled() -> CompiledSpec {\n");
+        rust.push_str("    CompiledSpec {\n");
+        rust.push_str(&format!(
+            "        state_names: vec![{}],\n",
+            strings(&self.state_names)
+        ));
+        rust.push_str(&format!("        start: {},\n", self.start));
+        rust.push_str(&format!(
+            "        class_names: vec![{}],\n",
+            strings(&self.class_names)
+        ));
+        rust.push_str(&format!("        chars: vec!{:?},\n", self.chars));
+
+        rust.push_str("        cells: vec![\n");
+        for cell in self.cells.iter() {
+            let next = match cell.next {
+                Next::State(state) => format!("Next::State({})", state),
+                Next::End => "Next::End".to_string(),
+                Next::Error(error) => format!("Next::Error(ErrorName::{:?})", error),
+            };
+            rust.push_str(&format!(
+                "            Cell {{ next: {}, push: {}, emit_number: {}, emit_operator: {} }},\n",
+                next, cell.push, cell.emit_number, cell.emit_operator
+            ));
+        }
+        rust.push_str("        ],\n");
+
+        rust.push_str("    }\n");
+        rust.push_str("}\n");
+        rust
+    }
+}
+
+// ====================
+// Errors
+// ====================
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SpecErrorKind {
+    InvalidLine,
+    InvalidString,
+    ReservedName(String),
+    DuplicateClass(String),
+    DuplicateCharacter(char),
+    DuplicateStart,
+    MissingStart,
+    UnknownClass(String),
+    UnknownState(String),
+    UnknownError(String),
+    UnknownToken(String),
+    DuplicateTransition { state: String, class: String },
+    MissingTransition { state: String, class: String },
+    PushAtEndOfInput,
+    // The end of input has to lead to `end` or an error
+    StateAfterEndOfInput(String),
+    // Operators can only be emitted for classes made of operator characters
+    OperatorFromNonOperator(String),
+    UnexpectedCharacterAtEndOfInput,
+}
+
+// Line numbers start at 1, line 0 is used for errors about the whole specification
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpecError {
+    pub line: usize,
+    pub kind: SpecErrorKind,
+}
+
+// ====================
+// Parsing
+// ====================
+
+const OPERATOR_CHARACTERS: &str = "+-*/";
+const RESERVED_NAMES: [&str; 5] = ["other", "eoi", "end", "error", "*"];
+
+// A word of a line, either bare or a quoted string
+#[derive(PartialEq, Debug)]
+pub(crate) enum Word {
+    Bare(String),
+    Quoted(String),
+}
+
+// Split a line into words, removing any comment
+pub(crate) fn split_words(line: &str) -> Result<Vec<Word>, SpecErrorKind> {
+    let mut words = vec![];
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c == '#' {
+            break;
+        } else if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('\\') => string.push('\\'),
+                        Some('"') => string.push('"'),
+                        Some('n') => string.push('\n'),
+                        Some('r') => string.push('\r'),
+                        Some('t') => string.push('\t'),
+                        _ => return Err(SpecErrorKind::InvalidString),
+                    },
+                    Some(c) => string.push(c),
+                    None => return Err(SpecErrorKind::InvalidString),
+                }
+            }
+            words.push(Word::Quoted(string));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' || c == '#' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            words.push(Word::Bare(word));
+        }
+    }
+
+    Ok(words)
+}
+
+// A transition as written in the specification
+struct Rule {
+    line: usize,
+    from: String,
+    class: String,
+    next: RuleNext,
+    push: bool,
+    emit_number: bool,
+    emit_operator: bool,
+}
+
+enum RuleNext {
+    State(String),
+    End,
+    Error(ErrorName),
+}
+
+fn parse_rule(line: usize, words: &[&str]) -> Result<Rule, SpecErrorKind> {
+    let (from, class, rest) = match words {
+        [from, class, "->", rest @ ..] if !rest.is_empty() => (from, class, rest),
+        _ => return Err(SpecErrorKind::InvalidLine),
+    };
+
+    let (next, mut rest) = match rest {
+        ["end", rest @ ..] => (RuleNext::End, rest),
+        ["error", name, rest @ ..] => match ErrorName::from_name(name) {
+            Some(error) => (RuleNext::Error(error), rest),
+            None => return Err(SpecErrorKind::UnknownError(name.to_string())),
+        },
+        ["error"] => return Err(SpecErrorKind::InvalidLine),
+        [state, rest @ ..] => (RuleNext::State(state.to_string()), rest),
+        [] => return Err(SpecErrorKind::InvalidLine),
+    };
+
+    let push = rest.first() == Some(&"push");
+    if push {
+        rest = &rest[1..];
+    }
+
+    let mut emit_number = false;
+    let mut emit_operator = false;
+    match rest {
+        [] => {}
+        ["emit", tokens @ ..] if !tokens.is_empty() => {
+            for token in tokens.iter() {
+                match *token {
+                    "number" if !emit_number && !emit_operator => emit_number = true,
+                    "operator" if !emit_operator => emit_operator = true,
+                    _ => return Err(SpecErrorKind::UnknownToken(token.to_string())),
+                }
+            }
+        }
+        _ => return Err(SpecErrorKind::InvalidLine),
+    }
+
+    Ok(Rule {
+        line,
+        from: from.to_string(),
+        class: class.to_string(),
+        next,
+        push,
+        emit_number,
+        emit_operator,
+    })
+}
+
+// Parse and check a specification
+pub fn compile(source: &str) -> Result<CompiledSpec, SpecError> {
+    let mut class_names: Vec<String> = vec![];
+    let mut class_chars: Vec<String> = vec![];
+    let mut chars: Vec<(char, usize)> = vec![];
+    let mut start: Option<String> = None;
+    let mut rules = vec![];
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let error = |kind| SpecError { line, kind };
+
+        let words = split_words(text).map_err(error)?;
+        let bare: Vec<&str> = words
+            .iter()
+            .map(|word| match word {
+                Word::Bare(word) => word.as_str(),
+                Word::Quoted(_) => "",
+            })
+            .collect();
+
+        match words.as_slice() {
+            [] => {}
+            [Word::Bare(keyword), Word::Bare(name), Word::Quoted(members)]
+                if keyword == "class" =>
+            {
+                if RESERVED_NAMES.contains(&name.as_str()) {
+                    return Err(error(SpecErrorKind::ReservedName(name.clone())));
+                }
+                if class_names.contains(name) {
+                    return Err(error(SpecErrorKind::DuplicateClass(name.clone())));
+                }
+                for c in members.chars() {
+                    if chars.iter().any(|&(existing, _)| existing == c) {
+                        return Err(error(SpecErrorKind::DuplicateCharacter(c)));
+                    }
+                    chars.push((c, class_names.len()));
+                }
+                class_names.push(name.clone());
+                class_chars.push(members.clone());
+            }
+            [Word::Bare(keyword), Word::Bare(state)] if keyword == "start" => {
+                if start.is_some() {
+                    return Err(error(SpecErrorKind::DuplicateStart));
+                }
+                start = Some(state.clone());
+            }
+            _ if words.iter().all(|word| matches!(word, Word::Bare(_))) => {
+                rules.push(parse_rule(line, &bare).map_err(error)?);
+            }
+            _ => return Err(error(SpecErrorKind::InvalidLine)),
+        }
+    }
+
+    let start = start.ok_or(SpecError {
+        line: 0,
+        kind: SpecErrorKind::MissingStart,
+    })?;
+
+    // States are numbered in order of appearance, starting with the start state
+    let mut state_names = vec![start];
+    for rule in rules.iter() {
+        if RESERVED_NAMES.contains(&rule.from.as_str()) {
+            return Err(SpecError {
+                line: rule.line,
+                kind: SpecErrorKind::ReservedName(rule.from.clone()),
+            });
+        }
+        if !state_names.contains(&rule.from) {
+            state_names.push(rule.from.clone());
+        }
+    }
+
+    class_names.push("other".to_string());
+    class_names.push("eoi".to_string());
+    let end_of_input = class_names.len() - 1;
+
+    // Fill the table, explicit classes first and then the wildcards
+    let mut cells: Vec<Option<Cell>> = vec![None; state_names.len() * class_names.len()];
+    let mut wildcard_states = vec![];
+    for wildcard in [false, true].iter() {
+        for rule in rules.iter().filter(|rule| (rule.class == "*") == *wildcard) {
+            let error = |kind| SpecError {
+                line: rule.line,
+                kind,
+            };
+
+            let state = state_names.iter().position(|s| *s == rule.from).unwrap();
+            if *wildcard {
+                // A second wildcard would match no class, and never be used
+                if wildcard_states.contains(&state) {
+                    return Err(error(SpecErrorKind::DuplicateTransition {
+                        state: rule.from.clone(),
+                        class: rule.class.clone(),
+                    }));
+                }
+                wildcard_states.push(state);
+            }
+            let next = match &rule.next {
+                RuleNext::State(name) => match state_names.iter().position(|s| s == name) {
+                    Some(state) => Next::State(state),
+                    None => return Err(error(SpecErrorKind::UnknownState(name.clone()))),
+                },
+                RuleNext::End => Next::End,
+                RuleNext::Error(error) => Next::Error(*error),
+            };
+            let cell = Cell {
+                next,
+                push: rule.push,
+                emit_number: rule.emit_number,
+                emit_operator: rule.emit_operator,
+            };
+
+            let classes: Vec<usize> = if *wildcard {
+                (0..class_names.len())
+                    .filter(|class| cells[state * class_names.len() + class].is_none())
+                    .collect()
+            } else {
+                match class_names.iter().position(|c| *c == rule.class) {
+                    Some(class) => vec![class],
+                    None => return Err(error(SpecErrorKind::UnknownClass(rule.class.clone()))),
+                }
+            };
+
+            for class in classes {
+                if class == end_of_input && cell.push {
+                    return Err(error(SpecErrorKind::PushAtEndOfInput));
+                }
+                if let (true, RuleNext::State(name)) = (class == end_of_input, &rule.next) {
+                    return Err(error(SpecErrorKind::StateAfterEndOfInput(name.clone())));
+                }
+                if class == end_of_input && next == Next::Error(ErrorName::UnexpectedCharacter) {
+                    return Err(error(SpecErrorKind::UnexpectedCharacterAtEndOfInput));
+                }
+                let is_operator_class = class < class_chars.len()
+                    && class_chars[class]
+                        .chars()
+                        .all(|c| OPERATOR_CHARACTERS.contains(c));
+                if cell.emit_operator && !is_operator_class {
+                    return Err(error(SpecErrorKind::OperatorFromNonOperator(
+                        class_names[class].clone(),
+                    )));
+                }
+
+                let slot = &mut cells[state * class_names.len() + class];
+                if slot.is_some() {
+                    return Err(error(SpecErrorKind::DuplicateTransition {
+                        state: rule.from.clone(),
+                        class: class_names[class].clone(),
+                    }));
+                }
+                *slot = Some(cell);
+            }
+        }
+    }
+
+    // Every state needs a transition for every class
+    let mut complete = vec![];
+    for (index, cell) in cells.into_iter().enumerate() {
+        match cell {
+            Some(cell) => complete.push(cell),
+            None => {
+                return Err(SpecError {
+                    line: 0,
+                    kind: SpecErrorKind::MissingTransition {
+                        state: state_names[index / class_names.len()].clone(),
+                        class: class_names[index % class_names.len()].clone(),
+                    },
+                })
+            }
+        }
+    }
+
+    chars.sort_unstable();
+
+    Ok(CompiledSpec {
+        state_names,
+        start: 0,
+        class_names,
+        chars,
+        cells: complete,
+    })
+}
@@ -0,0 +1,169 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::spec::{compile, Cell, CompiledSpec, ErrorName, Next};
+use crate::tokenize::{
+    get_operator_kind, ExpressionLexingError, LexingError, NumberLexingError, Token,
+};
+
+// The specification of the grammar lexed by `Lexer`
+pub const DEFAULT_SPEC: &str = include_str!("grammar.lex");
+
+// The default specification, compiled by build.rs
+mod generated {
+    use super::*;
+
+    include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
+}
+
+// Get the default specification generated at build time
+pub fn generated_spec() -> &'static CompiledSpec {
+    static SPEC: OnceLock<CompiledSpec> = OnceLock::new();
+    SPEC.get_or_init(generated::compiled)
+}
+
+// Compile the default specification at runtime
+pub fn default_spec() -> CompiledSpec {
+    // The same source is checked by build.rs
+    compile(DEFAULT_SPEC).unwrap()
+}
+
+pub(crate) fn to_lexing_error(error: ErrorName, c: Option<char>) -> LexingError {
+    match error {
+        ErrorName::ExpectedDigitAfterPoint => {
+            LexingError::IncorrectNumber(NumberLexingError::ExpectedDigitAfterPoint)
+        }
+        ErrorName::NonZeroIntegerBeforePoint => {
+            LexingError::IncorrectNumber(NumberLexingError::NonZeroIntegerBeforePoint)
+        }
+        ErrorName::MissingIntegerBeforePoint => {
+            LexingError::IncorrectNumber(NumberLexingError::MissingIntegerBeforePoint)
+        }
+        ErrorName::ExpectedPointAfterZero => {
+            LexingError::IncorrectNumber(NumberLexingError::ExpectedPointAfterZero)
+        }
+        ErrorName::UnexpectedCharacter => match c {
+            Some(c) => {
+                LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter(c))
+            }
+            None => unreachable!("specifications can't raise this error at the end of input"),
+        },
+        ErrorName::ExpectedNumber => {
+            LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber)
+        }
+        ErrorName::ExpectedOperator => {
+            LexingError::IncorrectExpression(ExpressionLexingError::ExpectedOperator)
+        }
+    }
+}
+
+// ====================
+// The lexer
+// ====================
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum SpecState {
+    State(usize),
+    End,
+    Error,
+}
+
+// A lexer running a compiled specification
+#[derive(Clone)]
+pub struct SpecLexer<'a> {
+    spec: &'a CompiledSpec,
+    buffer: Vec<char>,
+    state: SpecState,
+}
+
+impl<'a> SpecLexer<'a> {
+    pub fn new(spec: &'a CompiledSpec) -> Self {
+        Self {
+            spec,
+            buffer: Vec::new(),
+            state: SpecState::State(spec.start),
+        }
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.state == SpecState::End
+    }
+
+    // Get the name of the current state, or "end" or "error"
+    pub fn state_name(&self) -> &str {
+        match self.state {
+            SpecState::State(state) => &self.spec.state_names[state],
+            SpecState::End => "end",
+            SpecState::Error => "error",
+        }
+    }
+
+    // Drain the buffer and convert it to a number.
+    // A specification emitting something that isn't a number gets `ExpectedNumber`.
+    fn drain_buffer_to_decimal(&mut self) -> Result<f64, LexingError> {
+        let string: String = self.buffer.drain(..).collect();
+
+        f64::from_str(&string).map_err(|_| {
+            self.state = SpecState::Error;
+            LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber)
+        })
+    }
+
+    // Feed a character `Some(char)` to the lexer, or feed `None` for end of string.
+    pub fn feed(&mut self, c: Option<char>) -> Result<Vec<Token>, LexingError> {
+        let state = match self.state {
+            SpecState::State(state) => state,
+            SpecState::End | SpecState::Error => return Ok(vec![]),
+        };
+
+        let Cell {
+            next,
+            push,
+            emit_number,
+            emit_operator,
+        } = self.spec.cell(state, self.spec.class_of(c));
+
+        self.state = match next {
+            Next::State(state) => SpecState::State(state),
+            Next::End => SpecState::End,
+            Next::Error(error) => {
+                self.state = SpecState::Error;
+                return Err(to_lexing_error(error, c));
+            }
+        };
+
+        if push {
+            // Specifications can't push the end of input
+            self.buffer.extend(c);
+        }
+
+        let mut tokens = vec![];
+        if emit_number {
+            tokens.push(Token::Number(self.drain_buffer_to_decimal()?));
+        }
+        if emit_operator {
+            // Specifications can only emit operators for operator characters
+            if let Some(operator_kind) = c.and_then(get_operator_kind) {
+                tokens.push(Token::Operator(operator_kind));
+            }
+        }
+
+        Ok(tokens)
+    }
+}
+
+// Get the token list for string, using a compiled specification
+pub fn tokenize_with_spec(spec: &CompiledSpec, string: &str) -> Result<Vec<Token>, LexingError> {
+    let mut tokens = Vec::new();
+    let mut lexer = SpecLexer::new(spec);
+
+    for c in string.chars() {
+        tokens.append(&mut lexer.feed(Some(c))?);
+    }
+    tokens.append(&mut lexer.feed(None)?);
+
+    // Specifications always end at the end of input
+    assert!(lexer.is_ended());
+
+    Ok(tokens)
+}
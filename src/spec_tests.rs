@@ -0,0 +1,303 @@
+use crate::differential::random_input;
+use crate::input_class::InputClass;
+use crate::seeded;
+use crate::spec::{compile, ErrorName, Next, SpecError, SpecErrorKind};
+use crate::spec_lexer::{
+    default_spec, generated_spec, tokenize_with_spec, SpecLexer, DEFAULT_SPEC,
+};
+use crate::test_helpers::representative;
+use crate::tokenize::{tokenize, NumberLexingError, OperatorKind, State, Token};
+use crate::transitions::{transition, Emit, TransitionError};
+
+/// Characters of every class of the default specification, including a multi-byte one
+const ALPHABET: [char; 10] = ['0', '1', '9', '.', ' ', '+', '/', '-', 'x', 'é'];
+
+fn all_strings(len: usize) -> Vec<String> {
+    (0..len).fold(vec![String::new()], |strings, _| {
+        strings
+            .iter()
+            .flat_map(|s| ALPHABET.iter().map(move |&c| format!("{}{}", s, c)))
+            .collect()
+    })
+}
+
+fn compile_error(source: &str) -> SpecError {
+    match compile(source) {
+        Ok(_) => panic!("\n expected an error for:\n{}\n\n", source),
+        Err(error) => error,
+    }
+}
+
+/// The error of a transition, as named in specifications
+fn error_name(error: TransitionError) -> ErrorName {
+    match error {
+        TransitionError::IncorrectNumber(NumberLexingError::ExpectedDigitAfterPoint) => {
+            ErrorName::ExpectedDigitAfterPoint
+        }
+        TransitionError::IncorrectNumber(NumberLexingError::NonZeroIntegerBeforePoint) => {
+            ErrorName::NonZeroIntegerBeforePoint
+        }
+        TransitionError::IncorrectNumber(NumberLexingError::MissingIntegerBeforePoint) => {
+            ErrorName::MissingIntegerBeforePoint
+        }
+        TransitionError::IncorrectNumber(NumberLexingError::ExpectedPointAfterZero) => {
+            ErrorName::ExpectedPointAfterZero
+        }
+        TransitionError::ExpectedNumber => ErrorName::ExpectedNumber,
+        TransitionError::ExpectedOperator => ErrorName::ExpectedOperator,
+        TransitionError::UnexpectedCharacter => ErrorName::UnexpectedCharacter,
+    }
+}
+
+#[test]
+fn test_generated_spec_matches_runtime_spec() {
+    assert_eq!(generated_spec(), &default_spec());
+}
+
+#[test]
+fn test_default_spec_matches_transition_table() {
+    let spec = generated_spec();
+
+    // The end and error states of the table aren't states of specifications
+    for &state in State::ALL.iter() {
+        if state == State::End || state == State::Error {
+            continue;
+        }
+        let state_index = spec
+            .state_names
+            .iter()
+            .position(|name| *name == format!("{:?}", state))
+            .unwrap();
+
+        for &class in InputClass::ALL.iter() {
+            let t = transition(state, class);
+            let cell = spec.cell(state_index, spec.class_of(representative(class)));
+
+            let next = match (t.to, t.emit) {
+                (State::Error, Emit::Error(error)) => Next::Error(error_name(error)),
+                (State::End, _) => Next::End,
+                (to, _) => Next::State(
+                    spec.state_names
+                        .iter()
+                        .position(|name| *name == format!("{:?}", to))
+                        .unwrap(),
+                ),
+            };
+            let (emit_number, emit_operator) = match t.emit {
+                Emit::Nothing | Emit::Error(_) => (false, false),
+                Emit::Number => (true, false),
+                Emit::Operator => (false, true),
+                Emit::NumberAndOperator => (true, true),
+            };
+
+            let message = format!("\n state: {:?}, class: {:?}\n\n", state, class);
+            assert_eq!(cell.next, next, "{}", message);
+            assert_eq!(cell.push, t.push, "{}", message);
+            assert_eq!(cell.emit_number, emit_number, "{}", message);
+            assert_eq!(cell.emit_operator, emit_operator, "{}", message);
+        }
+    }
+}
+
+#[test]
+fn test_default_spec_agrees_with_lexer_on_short_inputs() {
+    let spec = default_spec();
+
+    for len in 0..=5 {
+        for input in all_strings(len) {
+            assert_eq!(
+                tokenize_with_spec(&spec, &input),
+                tokenize(&input),
+                "\n input: \"{}\"\n\n",
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_default_spec_agrees_with_lexer_on_random_inputs() {
+    let spec = generated_spec();
+
+    seeded::check(
+        10000,
+        || random_input(&ALPHABET, 29),
+        |input| assert_eq!(tokenize_with_spec(spec, input), tokenize(input)),
+    );
+}
+
+#[test]
+fn test_state_names() {
+    let spec = default_spec();
+    let mut lexer = SpecLexer::new(&spec);
+    assert_eq!(lexer.state_name(), "Initial");
+
+    for c in "0.5 ".chars() {
+        lexer.feed(Some(c)).unwrap();
+    }
+    assert_eq!(lexer.state_name(), "WhitespaceBeforeOperator");
+
+    lexer.feed(None).unwrap();
+    assert_eq!(lexer.state_name(), "end");
+}
+
+#[test]
+fn test_grammar_variant() {
+    // Tabs are whitespace too
+    let source = DEFAULT_SPEC.replace("class space \" \"", "class space \" \\t\"");
+    let spec = compile(&source).unwrap();
+
+    assert_eq!(
+        tokenize_with_spec(&spec, "1\t+ \t0.5"),
+        Ok(vec![
+            Token::Number(1.0),
+            Token::Operator(OperatorKind::Add),
+            Token::Number(0.5),
+        ])
+    );
+    assert!(tokenize("1\t+ \t0.5").is_err());
+}
+
+#[test]
+fn test_minimal_spec() {
+    // Single digit numbers separated by plus signs, without whitespace
+    let source = r#"
+        class digit "0123456789"
+        class plus "+"
+        start Operand
+
+        Operand digit -> Operator push
+        Operand * -> error ExpectedNumber
+        Operator plus -> Operand emit number operator
+        Operator eoi -> end emit number
+        Operator other -> error UnexpectedCharacter
+        Operator * -> error ExpectedOperator
+    "#;
+    let spec = compile(source).unwrap();
+
+    assert_eq!(
+        tokenize_with_spec(&spec, "1+2"),
+        Ok(vec![
+            Token::Number(1.0),
+            Token::Operator(OperatorKind::Add),
+            Token::Number(2.0),
+        ])
+    );
+    assert_eq!(tokenize_with_spec(&spec, "12"), tokenize("1 2"));
+    assert_eq!(tokenize_with_spec(&spec, "1+"), tokenize("1+"));
+}
+
+#[test]
+fn test_spec_errors() {
+    let header = "class digit \"0123456789\"\nclass plus \"+\"\nstart S\n";
+    let cases = vec![
+        ("S digit ->", 4, SpecErrorKind::InvalidLine),
+        ("class x \"ab", 4, SpecErrorKind::InvalidString),
+        (
+            "class other \"a\"",
+            4,
+            SpecErrorKind::ReservedName("other".to_string()),
+        ),
+        (
+            "class plus \"-\"",
+            4,
+            SpecErrorKind::DuplicateClass("plus".to_string()),
+        ),
+        (
+            "class minus \"+-\"",
+            4,
+            SpecErrorKind::DuplicateCharacter('+'),
+        ),
+        ("start T", 4, SpecErrorKind::DuplicateStart),
+        (
+            "S letter -> end",
+            4,
+            SpecErrorKind::UnknownClass("letter".to_string()),
+        ),
+        ("S * -> T", 4, SpecErrorKind::UnknownState("T".to_string())),
+        (
+            "S * -> error Oops",
+            4,
+            SpecErrorKind::UnknownError("Oops".to_string()),
+        ),
+        (
+            "S * -> end emit letter",
+            4,
+            SpecErrorKind::UnknownToken("letter".to_string()),
+        ),
+        (
+            "S * -> end emit operator number",
+            4,
+            SpecErrorKind::UnknownToken("number".to_string()),
+        ),
+        ("S eoi -> end push", 4, SpecErrorKind::PushAtEndOfInput),
+        (
+            "S eoi -> S",
+            4,
+            SpecErrorKind::StateAfterEndOfInput("S".to_string()),
+        ),
+        (
+            "S digit -> end emit operator",
+            4,
+            SpecErrorKind::OperatorFromNonOperator("digit".to_string()),
+        ),
+        (
+            "S eoi -> error UnexpectedCharacter",
+            4,
+            SpecErrorKind::UnexpectedCharacterAtEndOfInput,
+        ),
+        (
+            "S plus -> end\nS plus -> end",
+            5,
+            SpecErrorKind::DuplicateTransition {
+                state: "S".to_string(),
+                class: "plus".to_string(),
+            },
+        ),
+        (
+            "S * -> end\nS * -> error ExpectedNumber",
+            5,
+            SpecErrorKind::DuplicateTransition {
+                state: "S".to_string(),
+                class: "*".to_string(),
+            },
+        ),
+        (
+            "S plus -> end",
+            0,
+            SpecErrorKind::MissingTransition {
+                state: "S".to_string(),
+                class: "digit".to_string(),
+            },
+        ),
+    ];
+
+    for (rules, line, kind) in cases.into_iter() {
+        let source = format!("{}{}", header, rules);
+        assert_eq!(
+            compile_error(&source),
+            SpecError { line, kind },
+            "\n source:\n{}\n\n",
+            source
+        );
+    }
+
+    assert_eq!(
+        compile_error("S * -> end"),
+        SpecError {
+            line: 0,
+            kind: SpecErrorKind::MissingStart,
+        }
+    );
+}
+
+#[test]
+fn test_to_rust() {
+    let rust = default_spec().to_rust();
+
+    assert!(rust.starts_with("pub fn compiled() -> CompiledSpec {\n"));
+    assert!(rust.contains("        start: 0,\n"));
+    assert!(rust.contains(
+        "Cell { next: Next::Error(ErrorName::ExpectedNumber), push: false, emit_number: false, emit_operator: false },"
+    ));
+}
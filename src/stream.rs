@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::reader::{ByteLexer, ReadError, CHUNK_SIZE};
+use crate::tokenize::{Lexer, LexingError, Token};
+
+// ====================
+// Token stream over string chunks
+// ====================
+
+pub struct ChunkTokens<S> {
+    chunks: S,
+    lexer: Lexer,
+    tokens: VecDeque<Token>,
+    error: Option<LexingError>,
+    done: bool,
+}
+
+impl<S> ChunkTokens<S> {
+    // Feed a chunk to the lexer, or `None` for end of stream
+    fn feed_chunk(&mut self, chunk: Option<&str>) -> Result<(), LexingError> {
+        match chunk {
+            Some(chunk) => {
+                for c in chunk.chars() {
+                    self.tokens.extend(self.lexer.feed(Some(c))?);
+                }
+            }
+            None => {
+                self.done = true;
+                self.tokens.extend(self.lexer.feed(None)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S, T> Stream for ChunkTokens<S>
+where
+    S: Stream<Item = T> + Unpin,
+    T: AsRef<str>,
+{
+    type Item = Result<Token, LexingError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            // Tokens emitted before an error are returned first
+            if let Some(token) = this.tokens.pop_front() {
+                return Poll::Ready(Some(Ok(token)));
+            }
+            if let Some(error) = this.error.take() {
+                return Poll::Ready(Some(Err(error)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let chunk = match Pin::new(&mut this.chunks).poll_next(cx) {
+                Poll::Ready(chunk) => chunk,
+                Poll::Pending => return Poll::Pending,
+            };
+
+            if let Err(error) = this.feed_chunk(chunk.as_ref().map(|chunk| chunk.as_ref())) {
+                this.error = Some(error);
+                this.done = true;
+            }
+        }
+    }
+}
+
+// Lex a stream of string chunks as a single expression
+pub fn tokenize_chunks<S>(chunks: S) -> ChunkTokens<S> {
+    ChunkTokens {
+        chunks,
+        lexer: Lexer::new(),
+        tokens: VecDeque::new(),
+        error: None,
+        done: false,
+    }
+}
+
+// ====================
+// Token stream over an async reader
+// ====================
+
+pub struct AsyncReaderTokens<R> {
+    reader: R,
+    lexer: ByteLexer,
+    chunk: Vec<u8>,
+    error: Option<ReadError>,
+    done: bool,
+}
+
+impl<R: AsyncRead + Unpin> Stream for AsyncReaderTokens<R> {
+    type Item = Result<Token, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            // Tokens emitted before an error are returned first
+            if let Some(token) = this.lexer.tokens.pop_front() {
+                return Poll::Ready(Some(Ok(token)));
+            }
+            if let Some(error) = this.error.take() {
+                return Poll::Ready(Some(Err(error)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let mut buf = ReadBuf::new(&mut this.chunk);
+            let result = match Pin::new(&mut this.reader).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                    // == EOI ==
+                    this.done = true;
+                    this.lexer.feed_end().map_err(ReadError::from)
+                }
+                Poll::Ready(Ok(())) => {
+                    let read = buf.filled().len();
+                    this.lexer
+                        .feed_bytes(&this.chunk[..read])
+                        .map_err(ReadError::from)
+                }
+                Poll::Ready(Err(error)) if error.kind() == io::ErrorKind::Interrupted => Ok(()),
+                Poll::Ready(Err(error)) => Err(error.into()),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            if let Err(error) = result {
+                this.error = Some(error);
+                this.done = true;
+            }
+        }
+    }
+}
+
+// Lex the whole async reader as a single expression, reading it in chunks
+pub fn tokenize_async_reader<R>(reader: R) -> AsyncReaderTokens<R> {
+    AsyncReaderTokens {
+        reader,
+        lexer: ByteLexer::new(),
+        chunk: vec![0; CHUNK_SIZE],
+        error: None,
+        done: false,
+    }
+}
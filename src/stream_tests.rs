@@ -0,0 +1,111 @@
+use futures_util::stream::{self, StreamExt};
+use tokio::io::AsyncWriteExt;
+
+use crate::reader::ReadError;
+use crate::stream::{tokenize_async_reader, tokenize_chunks};
+use crate::tokenize::{
+    tokenize, EncodingLexingError, ExpressionLexingError, LexingError, NumberLexingError,
+    OperatorKind, Token,
+};
+
+/// Write the byte chunks to one end of an in-memory duplex stream, collecting the tokens read from the other end
+async fn tokenize_through_duplex(chunks: Vec<&'static [u8]>) -> Vec<Result<Token, ReadError>> {
+    // A tiny buffer makes the writer wait for the lexer
+    let (mut writer, reader) = tokio::io::duplex(4);
+
+    let write = async move {
+        for chunk in chunks {
+            writer.write_all(chunk).await.unwrap();
+        }
+        writer.shutdown().await.unwrap();
+    };
+    let read = tokenize_async_reader(reader).collect::<Vec<_>>();
+
+    tokio::join!(write, read).1
+}
+
+fn lexing_results(results: Vec<Result<Token, ReadError>>) -> Vec<Result<Token, LexingError>> {
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(token) => Ok(token),
+            Err(ReadError::Lexing(error)) => Err(error),
+            Err(ReadError::Io(error)) => panic!("unexpected io error: {}", error),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_async_reader_matches_tokenize() {
+    let input = "0.25 * 17 - 3 / 0.5 + 100";
+    let results = tokenize_through_duplex(vec![b"0.2", b"5 * 1", b"7 - 3 / 0.5 + 100"]).await;
+
+    let tokens: Result<Vec<_>, _> = lexing_results(results).into_iter().collect();
+    assert_eq!(tokens, tokenize(input));
+}
+
+#[tokio::test]
+async fn test_async_reader_split_character() {
+    // "é" is split between two writes
+    let results = tokenize_through_duplex(vec![b"1 + 2 \xc3", b"\xa9"]).await;
+
+    assert_eq!(
+        lexing_results(results),
+        vec![
+            Ok(Token::Number(1.0)),
+            Ok(Token::Operator(OperatorKind::Add)),
+            Ok(Token::Number(2.0)),
+            Err(LexingError::IncorrectExpression(
+                ExpressionLexingError::UnexpectedCharacter('é')
+            )),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_async_reader_invalid_utf8() {
+    let results = tokenize_through_duplex(vec![b"1 -", b" \xff"]).await;
+
+    assert_eq!(
+        lexing_results(results),
+        vec![
+            Ok(Token::Number(1.0)),
+            Ok(Token::Operator(OperatorKind::Subtract)),
+            Err(LexingError::IncorrectEncoding(
+                EncodingLexingError::InvalidUtf8 { offset: 4, len: 1 }
+            )),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_chunks() {
+    let chunks = stream::iter(vec!["0.", "5 +", " 1", "2"]);
+    let results: Vec<_> = tokenize_chunks(chunks).collect().await;
+
+    assert_eq!(
+        results,
+        vec![
+            Ok(Token::Number(0.5)),
+            Ok(Token::Operator(OperatorKind::Add)),
+            Ok(Token::Number(12.0)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_chunks_error() {
+    let chunks = stream::iter(vec!["3 * 0.".to_string(), " 1".to_string()]);
+    let results: Vec<_> = tokenize_chunks(chunks).collect().await;
+
+    assert_eq!(
+        results,
+        vec![
+            Ok(Token::Number(3.0)),
+            Ok(Token::Operator(OperatorKind::Multiply)),
+            Err(LexingError::IncorrectNumber(
+                NumberLexingError::ExpectedDigitAfterPoint
+            )),
+        ]
+    );
+}
@@ -0,0 +1,41 @@
+use crate::input_class::InputClass;
+use crate::snapshot::{LexerSnapshot, SNAPSHOT_VERSION};
+use crate::tokenize::{Backend, Lexer, NumberRepresentation, State};
+
+/// Characters of every input class, including a multi-byte one and a newline
+pub const ALPHABET: [char; 12] = ['0', '1', '9', '.', ' ', '+', '-', '*', '/', 'x', 'é', '\n'];
+
+/// Create a lexer in the given state, with a buffer that could lead to it
+pub fn lexer_in_state(state: State) -> Lexer {
+    let buffer = match state {
+        State::NumberZeroInteger => "0",
+        State::NumberPoint => "0.",
+        State::Number => "12",
+        _ => "",
+    };
+
+    Lexer::from_snapshot(&LexerSnapshot {
+        version: SNAPSHOT_VERSION,
+        state,
+        buffer: buffer.to_string(),
+        line_oriented: false,
+        line_start: false,
+        backend: Backend::HandWritten,
+        numbers: NumberRepresentation::Float,
+    })
+    .unwrap()
+}
+
+/// A character of each class
+pub fn representative(class: InputClass) -> Option<char> {
+    match class {
+        InputClass::ZeroDigit => Some('0'),
+        InputClass::NonZeroDigit => Some('7'),
+        InputClass::Point => Some('.'),
+        InputClass::Whitespace => Some(' '),
+        InputClass::Operator => Some('*'),
+        InputClass::Other => Some('x'),
+        InputClass::Newline => Some('\n'),
+        InputClass::EndOfInput => None,
+    }
+}
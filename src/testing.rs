@@ -0,0 +1,321 @@
+use std::ops::RangeInclusive;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::tokenize::{ExpressionLexingError, LexingError, NumberLexingError, OperatorKind, Token};
+
+// Characters which are in no class of the grammar
+pub const FOREIGN_CHARACTERS: [char; 6] = ['x', '(', ')', '_', 'é', '\t'];
+
+// Every error `Generator::invalid_input` can produce, `UnexpectedCharacter` standing for any
+// foreign character
+pub const GENERATED_ERRORS: [LexingError; 7] = [
+    LexingError::IncorrectNumber(NumberLexingError::ExpectedDigitAfterPoint),
+    LexingError::IncorrectNumber(NumberLexingError::NonZeroIntegerBeforePoint),
+    LexingError::IncorrectNumber(NumberLexingError::MissingIntegerBeforePoint),
+    LexingError::IncorrectNumber(NumberLexingError::ExpectedPointAfterZero),
+    LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter('x')),
+    LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber),
+    LexingError::IncorrectExpression(ExpressionLexingError::ExpectedOperator),
+];
+
+// ====================
+// Configuration
+// ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    // The number of numbers in an expression
+    pub numbers: RangeInclusive<usize>,
+    // The number of digits of an integer, and after the point of a decimal
+    pub integer_digits: RangeInclusive<usize>,
+    pub fraction_digits: RangeInclusive<usize>,
+    // The probability that a number is a decimal rather than an integer
+    pub decimal_probability: f64,
+    // The relative weights of `+`, `-`, `*` and `/`
+    pub operator_weights: [u32; 4],
+    // The number of spaces before, between and after tokens
+    pub spaces: RangeInclusive<usize>,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            numbers: 1..=4,
+            integer_digits: 1..=4,
+            fraction_digits: 1..=4,
+            decimal_probability: 0.5,
+            operator_weights: [1, 1, 1, 1],
+            spaces: 0..=1,
+        }
+    }
+}
+
+// Why a configuration can't generate the inputs it describes
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratorConfigError {
+    // A range to pick from, such as `3..=1`, is empty
+    EmptyRange(&'static str),
+    // Numbers need at least one digit, before or after the point
+    NoDigits(&'static str),
+    // The decimal probability isn't in `0.0..=1.0`
+    InvalidProbability(f64),
+    // Every operator weight is zero
+    NoOperatorWeight,
+}
+
+impl GeneratorConfig {
+    pub fn validate(&self) -> Result<(), GeneratorConfigError> {
+        let ranges = [
+            ("numbers", &self.numbers),
+            ("integer_digits", &self.integer_digits),
+            ("fraction_digits", &self.fraction_digits),
+            ("spaces", &self.spaces),
+        ];
+        for (name, range) in ranges.iter() {
+            if range.is_empty() {
+                return Err(GeneratorConfigError::EmptyRange(name));
+            }
+        }
+
+        if *self.integer_digits.start() == 0 {
+            return Err(GeneratorConfigError::NoDigits("integer_digits"));
+        }
+        if *self.fraction_digits.start() == 0 {
+            return Err(GeneratorConfigError::NoDigits("fraction_digits"));
+        }
+        if !(0.0..=1.0).contains(&self.decimal_probability) {
+            return Err(GeneratorConfigError::InvalidProbability(
+                self.decimal_probability,
+            ));
+        }
+        if self.operator_weights.iter().all(|&weight| weight == 0) {
+            return Err(GeneratorConfigError::NoOperatorWeight);
+        }
+
+        Ok(())
+    }
+}
+
+// ====================
+// Generator
+// ====================
+
+// A generated input, with what `tokenize` should return for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedInput {
+    pub input: String,
+    pub expected: Result<Vec<Token>, LexingError>,
+}
+
+pub struct Generator<R: Rng = StdRng> {
+    rng: R,
+    config: GeneratorConfig,
+    operators: WeightedIndex<u32>,
+}
+
+impl Generator<StdRng> {
+    // A generator producing the same inputs for the same seed
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new(StdRng::seed_from_u64(seed), GeneratorConfig::default())
+            .expect("the default configuration should be valid")
+    }
+}
+
+impl<R: Rng> Generator<R> {
+    pub fn new(rng: R, config: GeneratorConfig) -> Result<Self, GeneratorConfigError> {
+        config.validate()?;
+        let operators = WeightedIndex::new(config.operator_weights)
+            .map_err(|_| GeneratorConfigError::NoOperatorWeight)?;
+
+        Ok(Self {
+            rng,
+            config,
+            operators,
+        })
+    }
+
+    pub fn with_config(self, config: GeneratorConfig) -> Result<Self, GeneratorConfigError> {
+        Self::new(self.rng, config)
+    }
+
+    pub fn config(&self) -> &GeneratorConfig {
+        &self.config
+    }
+
+    fn digit(&mut self, non_zero: bool) -> char {
+        let low = if non_zero { 1 } else { 0 };
+        std::char::from_digit(self.rng.gen_range(low..10), 10).unwrap()
+    }
+
+    // An integer with `len` digits, or a length from the configuration
+    pub fn integer_string(&mut self, len: Option<usize>) -> String {
+        let len = len.unwrap_or_else(|| self.rng.gen_range(self.config.integer_digits.clone()));
+
+        (0..len).map(|i| self.digit(i == 0)).collect()
+    }
+
+    // A decimal with `len` digits after the point, or a length from the configuration
+    pub fn decimal_string(&mut self, len: Option<usize>) -> String {
+        let len = len.unwrap_or_else(|| self.rng.gen_range(self.config.fraction_digits.clone()));
+        let digits: String = (0..len).map(|_| self.digit(false)).collect();
+
+        format!("0.{}", digits)
+    }
+
+    pub fn number_string(&mut self) -> String {
+        if self.rng.gen_bool(self.config.decimal_probability) {
+            self.decimal_string(None)
+        } else {
+            self.integer_string(None)
+        }
+    }
+
+    pub fn operator(&mut self) -> OperatorKind {
+        match self.operators.sample(&mut self.rng) {
+            0 => OperatorKind::Add,
+            1 => OperatorKind::Subtract,
+            2 => OperatorKind::Multiply,
+            _ => OperatorKind::Divide,
+        }
+    }
+
+    // The lexemes of a valid expression, alternating numbers and operators
+    pub fn valid_lexemes(&mut self) -> Vec<String> {
+        // An expression has at least one number, even if the range starts at zero
+        let count = self.rng.gen_range(self.config.numbers.clone()).max(1);
+
+        let mut lexemes = vec![];
+        for i in 0..count {
+            if i != 0 {
+                let operator = operator_char(self.operator());
+                lexemes.push(operator.to_string());
+            }
+            lexemes.push(self.number_string());
+        }
+
+        lexemes
+    }
+
+    pub fn valid_token_sequence(&mut self) -> Vec<Token> {
+        lexemes_to_tokens(&self.valid_lexemes())
+    }
+
+    // Join the lexemes with a random number of spaces before, between and after them
+    pub fn pad_with_random_whitespaces(&mut self, lexemes: &[String]) -> String {
+        let mut string = self.spaces();
+        for lexeme in lexemes {
+            string.push_str(lexeme);
+            string.push_str(&self.spaces());
+        }
+
+        string
+    }
+
+    fn spaces(&mut self) -> String {
+        " ".repeat(self.rng.gen_range(self.config.spaces.clone()))
+    }
+
+    pub fn valid_input(&mut self) -> GeneratedInput {
+        let lexemes = self.valid_lexemes();
+
+        GeneratedInput {
+            input: self.pad_with_random_whitespaces(&lexemes),
+            expected: Ok(lexemes_to_tokens(&lexemes)),
+        }
+    }
+
+    // An input raising one of `GENERATED_ERRORS`, picked at random
+    pub fn invalid_input(&mut self) -> GeneratedInput {
+        let error = GENERATED_ERRORS[self.rng.gen_range(0..GENERATED_ERRORS.len())];
+
+        let error = match error {
+            LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter(_)) => {
+                let c = FOREIGN_CHARACTERS[self.rng.gen_range(0..FOREIGN_CHARACTERS.len())];
+                LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter(c))
+            }
+            error => error,
+        };
+
+        self.input_with_error(error)
+    }
+
+    // An otherwise valid input, with one number replaced by a lexeme raising `error`.
+    // Panics for encoding errors and for unexpected characters of the grammar.
+    pub fn input_with_error(&mut self, error: LexingError) -> GeneratedInput {
+        let replacement = match error {
+            LexingError::IncorrectNumber(NumberLexingError::ExpectedDigitAfterPoint) => {
+                "0.".to_string()
+            }
+            LexingError::IncorrectNumber(NumberLexingError::NonZeroIntegerBeforePoint) => {
+                format!("{}.{}", self.integer_string(None), self.digit(false))
+            }
+            LexingError::IncorrectNumber(NumberLexingError::MissingIntegerBeforePoint) => {
+                format!(".{}", self.integer_string(None))
+            }
+            LexingError::IncorrectNumber(NumberLexingError::ExpectedPointAfterZero) => {
+                format!("0{}", self.integer_string(None))
+            }
+            LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter(c)) => {
+                assert!(
+                    !is_grammar_character(c),
+                    "{:?} is a character of the grammar",
+                    c
+                );
+                c.to_string()
+            }
+            LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber) => {
+                operator_char(self.operator()).to_string()
+            }
+            LexingError::IncorrectExpression(ExpressionLexingError::ExpectedOperator) => {
+                format!("{} {}", self.number_string(), self.number_string())
+            }
+            LexingError::IncorrectEncoding(_) => {
+                panic!("strings can't raise encoding errors")
+            }
+        };
+
+        // Numbers are at even positions
+        let mut lexemes = self.valid_lexemes();
+        let index = 2 * self.rng.gen_range(0..lexemes.len().div_ceil(2));
+        lexemes[index] = replacement;
+
+        GeneratedInput {
+            input: self.pad_with_random_whitespaces(&lexemes),
+            expected: Err(error),
+        }
+    }
+}
+
+// ====================
+// Helpers
+// ====================
+
+pub fn operator_char(operator: OperatorKind) -> char {
+    match operator {
+        OperatorKind::Add => '+',
+        OperatorKind::Subtract => '-',
+        OperatorKind::Multiply => '*',
+        OperatorKind::Divide => '/',
+    }
+}
+
+fn is_grammar_character(c: char) -> bool {
+    c.is_ascii_digit() || c == '.' || c == ' ' || "+-*/".contains(c)
+}
+
+// The tokens of valid lexemes
+fn lexemes_to_tokens(lexemes: &[String]) -> Vec<Token> {
+    lexemes
+        .iter()
+        .map(|lexeme| match lexeme.as_str() {
+            "+" => Token::Operator(OperatorKind::Add),
+            "-" => Token::Operator(OperatorKind::Subtract),
+            "*" => Token::Operator(OperatorKind::Multiply),
+            "/" => Token::Operator(OperatorKind::Divide),
+            number => Token::Number(number.parse().unwrap()),
+        })
+        .collect()
+}
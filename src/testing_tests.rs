@@ -0,0 +1,166 @@
+use crate::testing::{Generator, GeneratorConfig, GeneratorConfigError, GENERATED_ERRORS};
+use crate::tokenize::{
+    tokenize, ExpressionLexingError, LexingError, NumberLexingError, OperatorKind, Token,
+};
+
+/// The number of inputs generated by each test
+const REPEAT_COUNT: i32 = 1000;
+
+#[test]
+fn test_same_seed_same_inputs() {
+    let mut first = Generator::from_seed(42);
+    let mut second = Generator::from_seed(42);
+
+    for _ in 0..100 {
+        assert_eq!(first.valid_input(), second.valid_input());
+        assert_eq!(first.invalid_input(), second.invalid_input());
+    }
+}
+
+#[test]
+fn test_valid_inputs() {
+    let mut generator = Generator::from_seed(1);
+
+    for _ in 0..REPEAT_COUNT {
+        let generated = generator.valid_input();
+        assert_eq!(
+            tokenize(&generated.input),
+            generated.expected,
+            "\n input: \"{}\"\n\n",
+            generated.input
+        );
+    }
+}
+
+#[test]
+fn test_invalid_inputs() {
+    let mut generator = Generator::from_seed(2);
+
+    for _ in 0..REPEAT_COUNT {
+        let generated = generator.invalid_input();
+        assert!(generated.expected.is_err());
+        assert_eq!(
+            tokenize(&generated.input),
+            generated.expected,
+            "\n input: \"{}\"\n\n",
+            generated.input
+        );
+    }
+}
+
+#[test]
+fn test_every_error_is_generated() {
+    let mut generator = Generator::from_seed(3);
+
+    for &error in GENERATED_ERRORS.iter() {
+        for _ in 0..REPEAT_COUNT {
+            let generated = generator.input_with_error(error);
+            assert_eq!(
+                tokenize(&generated.input),
+                Err(error),
+                "\n input: \"{}\"\n\n",
+                generated.input
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_grammar_character_is_not_unexpected() {
+    Generator::from_seed(4).input_with_error(LexingError::IncorrectExpression(
+        ExpressionLexingError::UnexpectedCharacter('+'),
+    ));
+}
+
+#[test]
+fn test_configuration() {
+    let config = GeneratorConfig {
+        numbers: 3..=3,
+        integer_digits: 2..=2,
+        decimal_probability: 0.0,
+        operator_weights: [0, 0, 1, 0],
+        spaces: 2..=2,
+        ..GeneratorConfig::default()
+    };
+    let mut generator = Generator::from_seed(5).with_config(config).unwrap();
+
+    let generated = generator.valid_input();
+    let tokens = generated.expected.unwrap();
+
+    assert_eq!(generated.input.len(), 3 * 2 + 2 + 6 * 2);
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(tokens[1], Token::Operator(OperatorKind::Multiply));
+    assert_eq!(tokens[3], Token::Operator(OperatorKind::Multiply));
+    for token in tokens.iter().step_by(2) {
+        match token {
+            Token::Number(number) => assert!((10.0..100.0).contains(number)),
+            _ => panic!("{:?}", token),
+        }
+    }
+
+    let decimal = generator.decimal_string(Some(3));
+    assert!(decimal.starts_with("0.") && decimal.len() == 5);
+    assert_eq!(
+        tokenize(&format!("{}.", generator.integer_string(Some(1)))),
+        Err(LexingError::IncorrectNumber(
+            NumberLexingError::NonZeroIntegerBeforePoint
+        ))
+    );
+}
+
+#[test]
+fn test_invalid_configurations() {
+    #[allow(clippy::reversed_empty_ranges)]
+    let cases = vec![
+        (
+            GeneratorConfig {
+                integer_digits: 0..=3,
+                ..GeneratorConfig::default()
+            },
+            GeneratorConfigError::NoDigits("integer_digits"),
+        ),
+        (
+            GeneratorConfig {
+                fraction_digits: 0..=3,
+                ..GeneratorConfig::default()
+            },
+            GeneratorConfigError::NoDigits("fraction_digits"),
+        ),
+        (
+            GeneratorConfig {
+                spaces: 3..=1,
+                ..GeneratorConfig::default()
+            },
+            GeneratorConfigError::EmptyRange("spaces"),
+        ),
+        (
+            GeneratorConfig {
+                decimal_probability: 1.5,
+                ..GeneratorConfig::default()
+            },
+            GeneratorConfigError::InvalidProbability(1.5),
+        ),
+        (
+            GeneratorConfig {
+                operator_weights: [0; 4],
+                ..GeneratorConfig::default()
+            },
+            GeneratorConfigError::NoOperatorWeight,
+        ),
+    ];
+
+    for (config, error) in cases.into_iter() {
+        assert_eq!(config.validate(), Err(error.clone()));
+        assert_eq!(
+            Generator::from_seed(6).with_config(config).err(),
+            Some(error)
+        );
+    }
+
+    let nan = GeneratorConfig {
+        decimal_probability: f64::NAN,
+        ..GeneratorConfig::default()
+    };
+    assert!(nan.validate().is_err());
+}
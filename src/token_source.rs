@@ -0,0 +1,39 @@
+use crate::spec::CompiledSpec;
+use crate::spec_lexer::tokenize_with_spec;
+use crate::tokenize::{tokenize_with, Lexer, LexingError, Token};
+
+// ====================
+// Token source
+// ====================
+
+// An implementation of the lexer, turning a whole string into tokens.
+// Anything consuming tokens can take a `TokenSource` to stay independent of the implementation,
+// like the evaluators of `crate::evaluate` and the ported tests.
+pub trait TokenSource {
+    fn tokenize(&mut self, string: &str) -> Result<Vec<Token>, LexingError>;
+}
+
+// The state machine, with the backend and mode it was created with.
+// The lexer is reset first, so it can be reused for many strings.
+impl TokenSource for Lexer {
+    fn tokenize(&mut self, string: &str) -> Result<Vec<Token>, LexingError> {
+        self.reset();
+        tokenize_with(self, string)
+    }
+}
+
+impl TokenSource for CompiledSpec {
+    fn tokenize(&mut self, string: &str) -> Result<Vec<Token>, LexingError> {
+        tokenize_with_spec(self, string)
+    }
+}
+
+// Plain functions such as `tokenize::tokenize`
+impl<F> TokenSource for F
+where
+    F: FnMut(&str) -> Result<Vec<Token>, LexingError>,
+{
+    fn tokenize(&mut self, string: &str) -> Result<Vec<Token>, LexingError> {
+        self(string)
+    }
+}
@@ -1,5 +1,12 @@
+#![allow(clippy::needless_return)]
+
 use std::str::FromStr;
 
+use num_bigint::BigInt;
+
+use crate::decimal::Decimal;
+use crate::scan;
+
 // ====================
 // Helper types
 // ====================
@@ -16,18 +23,27 @@ pub enum OperatorKind {
 // Token
 // ====================
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Operator(OperatorKind),
     Number(f64),
+    // The exact number written, emitted instead of `Number` by lexers created with
+    // `NumberRepresentation::Decimal`
+    Decimal(Decimal),
+    // A number written without a point, emitted instead of `Number` by lexers created with
+    // `NumberRepresentation::Exact`
+    Integer(BigInt),
+    // Emitted at the end of every successfully lexed line in line-oriented mode
+    EndOfExpression,
 }
 
 // ====================
 // State enum
 // ====================
 
-#[derive(Copy, Clone, PartialEq)]
-enum State {
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum State {
     Initial,
     WhitespaceBeforeOperator,
     NumberZeroInteger,
@@ -37,6 +53,18 @@ enum State {
     Error,
 }
 
+impl State {
+    pub const ALL: [State; 7] = [
+        State::Initial,
+        State::WhitespaceBeforeOperator,
+        State::NumberZeroInteger,
+        State::NumberPoint,
+        State::Number,
+        State::End,
+        State::Error,
+    ];
+}
+
 // ====================
 // Errors
 // ====================
@@ -56,26 +84,69 @@ pub enum ExpressionLexingError {
     ExpectedOperator,
 }
 
+// Byte offsets are counted from the start of the input stream
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum EncodingLexingError {
+    InvalidUtf8 { offset: usize, len: usize },
+    IncompleteUtf8 { offset: usize },
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum LexingError {
     IncorrectNumber(NumberLexingError),
     IncorrectExpression(ExpressionLexingError),
+    IncorrectEncoding(EncodingLexingError),
 }
 
 // ====================
 // The lexer & implementation
 // ====================
 
+// The implementation of the state machine used by a lexer, both behave identically
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Backend {
+    // The `match` on the state in `Lexer::feed`
+    HandWritten,
+    // Lookups in the tables of `crate::dfa`
+    TableDriven,
+}
+
+// The type of the numbers in the tokens emitted by a lexer
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NumberRepresentation {
+    // `Token::Number`, the nearest float
+    Float,
+    // `Token::Decimal`, exactly as written
+    Decimal,
+    // `Token::Integer` for numbers without a point, `Token::Decimal` for the others
+    Exact,
+}
+
+#[derive(Clone)]
 pub struct Lexer {
-    buffer: Vec<char>,
-    state: State,
+    pub(crate) buffer: Vec<char>,
+    pub(crate) state: State,
+    // Whether a newline ends the current expression and starts a new one
+    pub(crate) line_oriented: bool,
+    // Whether nothing was fed since the start of the current line
+    pub(crate) line_start: bool,
+    pub(crate) backend: Backend,
+    pub(crate) numbers: NumberRepresentation,
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 fn is_digit(c: char) -> bool {
-    c.is_digit(10)
+    c.is_ascii_digit()
 }
 
-fn get_operator_kind(c: char) -> Option<OperatorKind> {
+pub(crate) fn get_operator_kind(c: char) -> Option<OperatorKind> {
     match c {
         '-' => Some(OperatorKind::Subtract),
         '+' => Some(OperatorKind::Add),
@@ -91,16 +162,64 @@ impl Lexer {
         Self {
             buffer: Vec::new(),
             state: State::Initial,
+            line_oriented: false,
+            line_start: true,
+            backend: Backend::HandWritten,
+            numbers: NumberRepresentation::Float,
         }
     }
 
+    // Create a new lexer instance using the given state machine implementation
+    pub fn with_backend(backend: Backend) -> Self {
+        Self::new().backend(backend)
+    }
+
+    // Create a new lexer instance emitting numbers with the given representation
+    pub fn with_numbers(numbers: NumberRepresentation) -> Self {
+        Self::new().numbers(numbers)
+    }
+
+    // Create a new lexer instance which lexes every line as a separate expression
+    pub fn line_oriented() -> Self {
+        Self::new().by_line(true)
+    }
+
+    // Use the given state machine implementation, e.g. `Lexer::line_oriented().backend(...)`
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    // Emit numbers with the given representation
+    pub fn numbers(mut self, numbers: NumberRepresentation) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    // Lex every line as a separate expression, or the whole input as one
+    pub fn by_line(mut self, line_oriented: bool) -> Self {
+        self.line_oriented = line_oriented;
+        self
+    }
+
+    // Return to the initial state to lex a new expression, keeping the buffer capacity
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.state = State::Initial;
+        self.line_start = true;
+    }
+
     pub fn is_ended(&self) -> bool {
         self.state == State::End
     }
 
+    pub fn state(&self) -> State {
+        self.state
+    }
+
     // Helper function
     // Drain all of the characters in self.buffer and convert it to a number.
-    fn drain_buffer_to_decimal(&mut self) -> f64 {
+    pub(crate) fn drain_buffer_to_decimal(&mut self) -> f64 {
         // Drain the characrers in the buffer and convert them to a string
         let string: String = self.buffer.drain(..).collect();
 
@@ -111,8 +230,115 @@ impl Lexer {
         number
     }
 
+    // Drain the characters in self.buffer into a number token of the lexer's representation
+    pub(crate) fn drain_buffer_to_number(&mut self) -> Token {
+        match self.numbers {
+            NumberRepresentation::Float => Token::Number(self.drain_buffer_to_decimal()),
+            NumberRepresentation::Exact if !self.buffer.contains(&'.') => {
+                let string: String = self.buffer.drain(..).collect();
+                Token::Integer(string.parse().unwrap())
+            }
+            NumberRepresentation::Decimal | NumberRepresentation::Exact => {
+                let string: String = self.buffer.drain(..).collect();
+                Token::Decimal(string.parse().unwrap())
+            }
+        }
+    }
+
     // Feed a character `Some(char)` to the lexer, or feed `None` for end of string.
     pub fn feed(&mut self, c: Option<char>) -> Result<Vec<Token>, LexingError> {
+        if self.line_oriented {
+            self.feed_line_oriented(c)
+        } else {
+            self.feed_expression(c)
+        }
+    }
+
+    // Feed a whole string, stopping at the first error. Runs of digits in a number and of
+    // spaces before a number or an operator are skipped in bulk with either backend, as they
+    // never change the state.
+    pub fn feed_str(&mut self, string: &str) -> Result<Vec<Token>, LexingError> {
+        let bytes = string.as_bytes();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let end = match self.state {
+                State::Number => scan::digit_run_end(bytes, i),
+                State::Initial | State::WhitespaceBeforeOperator => scan::next_non_space(bytes, i),
+                _ => i,
+            };
+            if end > i {
+                if self.state == State::Number {
+                    self.buffer
+                        .extend(bytes[i..end].iter().map(|&byte| byte as char));
+                }
+                self.line_start = false;
+                i = end;
+                continue;
+            }
+
+            // Runs end at ASCII bytes, so `i` is always at a character boundary
+            let c = string[i..].chars().next().unwrap();
+            tokens.append(&mut self.feed(Some(c))?);
+            i += c.len_utf8();
+        }
+
+        Ok(tokens)
+    }
+
+    // Feed a character in line-oriented mode.
+    // Every line produces either an error or its tokens followed by `Token::EndOfExpression`.
+    fn feed_line_oriented(&mut self, c: Option<char>) -> Result<Vec<Token>, LexingError> {
+        if self.state == State::End {
+            return Ok(vec![]);
+        }
+
+        match c {
+            Some('\n') => self.end_line(),
+            Some(c) => self.feed_expression(Some(c)),
+            None => {
+                // Input ending with a newline doesn't have an extra empty line
+                let result = if self.line_start {
+                    Ok(vec![])
+                } else {
+                    self.end_line()
+                };
+                self.state = State::End;
+                result
+            }
+        }
+    }
+
+    // Finish the current line as if it was the end of input, then start a new expression
+    fn end_line(&mut self) -> Result<Vec<Token>, LexingError> {
+        let result = match self.state {
+            // The error was already returned for this line
+            State::Error => Ok(vec![]),
+            _ => self.feed_expression(None).map(|mut tokens| {
+                tokens.push(Token::EndOfExpression);
+                tokens
+            }),
+        };
+
+        self.reset();
+        result
+    }
+
+    // Feed a character to the state machine of a single expression
+    fn feed_expression(&mut self, c: Option<char>) -> Result<Vec<Token>, LexingError> {
+        if c.is_some() {
+            self.line_start = false;
+        }
+
+        match self.backend {
+            Backend::HandWritten => self.feed_hand_written(c),
+            Backend::TableDriven => self.feed_table_driven(c),
+        }
+    }
+
+    // Feed a character to the hand-written state machine
+    fn feed_hand_written(&mut self, c: Option<char>) -> Result<Vec<Token>, LexingError> {
         // Process the remaining states
         match self.state {
             // If the state is end or error, return nothing
@@ -140,7 +366,7 @@ impl Lexer {
                         // == whitespace ==
                         // Stay on the same state, return nothing
                         return Ok(vec![]);
-                    } else if let Some(_) = get_operator_kind(c) {
+                    } else if get_operator_kind(c).is_some() {
                         // !! error !!
                         // Unexpected operator
                         self.state = State::Error;
@@ -193,13 +419,13 @@ impl Lexer {
                         // == whitespace ==
                         // Switch to first whitespace state, return number token
                         self.state = State::WhitespaceBeforeOperator;
-                        return Ok(vec![Token::Number(self.drain_buffer_to_decimal())]);
+                        return Ok(vec![self.drain_buffer_to_number()]);
                     } else if let Some(operator_kind) = get_operator_kind(c) {
                         // == operator ==
                         // Switch to operator (initial) state, return number token and operator token
                         self.state = State::Initial;
                         return Ok(vec![
-                            Token::Number(self.drain_buffer_to_decimal()),
+                            self.drain_buffer_to_number(),
                             Token::Operator(operator_kind),
                         ]);
                     } else {
@@ -214,7 +440,7 @@ impl Lexer {
                     // == EOI ==
                     // Switch to end state, return number token
                     self.state = State::End;
-                    return Ok(vec![Token::Number(self.drain_buffer_to_decimal())]);
+                    return Ok(vec![self.drain_buffer_to_number()]);
                 }
             }
 
@@ -259,13 +485,13 @@ impl Lexer {
                         // == whitespace ==
                         // Switch to first whitespace state, return number token
                         self.state = State::WhitespaceBeforeOperator;
-                        return Ok(vec![Token::Number(self.drain_buffer_to_decimal())]);
+                        return Ok(vec![self.drain_buffer_to_number()]);
                     } else if let Some(operator_kind) = get_operator_kind(c) {
                         // == operator ==
                         // Switch to operator state, return number token and operator token
                         self.state = State::Initial;
                         return Ok(vec![
-                            Token::Number(self.drain_buffer_to_decimal()),
+                            self.drain_buffer_to_number(),
                             Token::Operator(operator_kind),
                         ]);
                     } else if c == '.' {
@@ -287,7 +513,7 @@ impl Lexer {
                     // == EOI ==
                     // Switch to end state, return number token
                     self.state = State::End;
-                    return Ok(vec![Token::Number(self.drain_buffer_to_decimal())]);
+                    return Ok(vec![self.drain_buffer_to_number()]);
                 }
             }
 
@@ -335,22 +561,24 @@ impl Lexer {
 // ====================
 
 pub fn tokenize(string: &str) -> Result<Vec<Token>, LexingError> {
+    tokenize_with(&mut Lexer::new(), string)
+}
+
+// Same as `tokenize`, but using an existing lexer so that its buffer can be reused.
+// The lexer is reset first, whatever an earlier call left it in.
+pub fn tokenize_with(lexer: &mut Lexer, string: &str) -> Result<Vec<Token>, LexingError> {
     let mut tokens = Vec::new();
-    let mut lexer = Lexer::new();
+    lexer.reset();
 
-    // Feed characters, one at a time
-    for c in string.chars().into_iter() {
-        let mut result = lexer.feed(Some(c))?;
-        // If a token was emitted, add it to the list
-        tokens.append(&mut result);
-    }
+    // Feed characters, skipping the runs of digits and spaces in bulk
+    tokens.append(&mut lexer.feed_str(string)?);
 
     // Feed EOI
     let mut result = lexer.feed(None)?;
     tokens.append(&mut result);
 
     // Just in case, make sure the lexer is ended
-    assert_eq!(lexer.is_ended(), true);
+    assert!(lexer.is_ended());
 
     Ok(tokens)
 }
@@ -0,0 +1,250 @@
+use crate::input_class::InputClass;
+use crate::tokenize::{ExpressionLexingError, LexingError, NumberLexingError, State};
+
+// ====================
+// Transition table types
+// ====================
+
+// An error raised by a transition, without the character that caused it
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TransitionError {
+    IncorrectNumber(NumberLexingError),
+    ExpectedNumber,
+    ExpectedOperator,
+    // Only raised for characters, never for the end of input
+    UnexpectedCharacter,
+}
+
+impl TransitionError {
+    // Get the lexing error for the character `Some(char)`, or `None` for end of string
+    pub fn to_lexing_error(self, c: Option<char>) -> LexingError {
+        match self {
+            TransitionError::IncorrectNumber(error) => LexingError::IncorrectNumber(error),
+            TransitionError::ExpectedNumber => {
+                LexingError::IncorrectExpression(ExpressionLexingError::ExpectedNumber)
+            }
+            TransitionError::ExpectedOperator => {
+                LexingError::IncorrectExpression(ExpressionLexingError::ExpectedOperator)
+            }
+            TransitionError::UnexpectedCharacter => match c {
+                Some(c) => {
+                    LexingError::IncorrectExpression(ExpressionLexingError::UnexpectedCharacter(c))
+                }
+                None => unreachable!("the end of input is never an unexpected character"),
+            },
+        }
+    }
+}
+
+// What a transition returns from `Lexer::feed`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Emit {
+    Nothing,
+    // The number in the buffer
+    Number,
+    // The operator that was fed
+    Operator,
+    NumberAndOperator,
+    Error(TransitionError),
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Transition {
+    pub from: State,
+    pub input: InputClass,
+    pub to: State,
+    // Whether the character is pushed to the number buffer
+    pub push: bool,
+    pub emit: Emit,
+}
+
+// ====================
+// The transition table
+// ====================
+
+// Get the transition taken by `Lexer::feed` for an input class in a state.
+// This is the one description of the grammar: the DFA tables and the expected inputs are built
+// from it, and `feed` and src/grammar.lex are tested against it.
+pub fn transition(from: State, input: InputClass) -> Transition {
+    use InputClass::*;
+
+    let error = |error: TransitionError| (State::Error, false, Emit::Error(error));
+    let number_error = |error: NumberLexingError| {
+        (
+            State::Error,
+            false,
+            Emit::Error(TransitionError::IncorrectNumber(error)),
+        )
+    };
+
+    let (to, push, emit) = match (from, input) {
+        (State::End, _) => (State::End, false, Emit::Nothing),
+        (State::Error, _) => (State::Error, false, Emit::Nothing),
+
+        // Initial state
+        (State::Initial, ZeroDigit) => (State::NumberZeroInteger, true, Emit::Nothing),
+        (State::Initial, NonZeroDigit) => (State::Number, true, Emit::Nothing),
+        (State::Initial, Whitespace) => (State::Initial, false, Emit::Nothing),
+        (State::Initial, Operator) => error(TransitionError::ExpectedNumber),
+        (State::Initial, Point) => number_error(NumberLexingError::MissingIntegerBeforePoint),
+        (State::Initial, Other | Newline) => error(TransitionError::UnexpectedCharacter),
+        (State::Initial, EndOfInput) => error(TransitionError::ExpectedNumber),
+
+        // Number (zero)
+        (State::NumberZeroInteger, ZeroDigit) | (State::NumberZeroInteger, NonZeroDigit) => {
+            number_error(NumberLexingError::ExpectedPointAfterZero)
+        }
+        (State::NumberZeroInteger, Point) => (State::NumberPoint, true, Emit::Nothing),
+        (State::NumberZeroInteger, Whitespace) => {
+            (State::WhitespaceBeforeOperator, false, Emit::Number)
+        }
+        (State::NumberZeroInteger, Operator) => (State::Initial, false, Emit::NumberAndOperator),
+        (State::NumberZeroInteger, Other | Newline) => error(TransitionError::UnexpectedCharacter),
+        (State::NumberZeroInteger, EndOfInput) => (State::End, false, Emit::Number),
+
+        // Number (point)
+        (State::NumberPoint, ZeroDigit) | (State::NumberPoint, NonZeroDigit) => {
+            (State::Number, true, Emit::Nothing)
+        }
+        (State::NumberPoint, _) => number_error(NumberLexingError::ExpectedDigitAfterPoint),
+
+        // Number (decimals)
+        (State::Number, ZeroDigit) | (State::Number, NonZeroDigit) => {
+            (State::Number, true, Emit::Nothing)
+        }
+        (State::Number, Whitespace) => (State::WhitespaceBeforeOperator, false, Emit::Number),
+        (State::Number, Operator) => (State::Initial, false, Emit::NumberAndOperator),
+        (State::Number, Point) => number_error(NumberLexingError::NonZeroIntegerBeforePoint),
+        (State::Number, Other | Newline) => error(TransitionError::UnexpectedCharacter),
+        (State::Number, EndOfInput) => (State::End, false, Emit::Number),
+
+        // First whitespace
+        (State::WhitespaceBeforeOperator, Whitespace) => {
+            (State::WhitespaceBeforeOperator, false, Emit::Nothing)
+        }
+        (State::WhitespaceBeforeOperator, Operator) => (State::Initial, false, Emit::Operator),
+        (State::WhitespaceBeforeOperator, ZeroDigit)
+        | (State::WhitespaceBeforeOperator, NonZeroDigit) => {
+            error(TransitionError::ExpectedOperator)
+        }
+        (State::WhitespaceBeforeOperator, Point | Other | Newline) => {
+            error(TransitionError::UnexpectedCharacter)
+        }
+        (State::WhitespaceBeforeOperator, EndOfInput) => (State::End, false, Emit::Nothing),
+    };
+
+    Transition {
+        from,
+        input,
+        to,
+        push,
+        emit,
+    }
+}
+
+// Get every transition, for every state and input class
+pub fn transition_table() -> Vec<Transition> {
+    State::ALL
+        .iter()
+        .flat_map(|&from| {
+            InputClass::ALL
+                .iter()
+                .map(move |&input| transition(from, input))
+        })
+        .collect()
+}
+
+// ====================
+// Exporters
+// ====================
+
+fn input_label(input: InputClass) -> &'static str {
+    match input {
+        InputClass::ZeroDigit => "0",
+        InputClass::NonZeroDigit => "1-9",
+        InputClass::Point => ".",
+        InputClass::Whitespace => "space",
+        InputClass::Operator => "operator",
+        InputClass::Other => "other",
+        InputClass::Newline => "newline",
+        InputClass::EndOfInput => "EOI",
+    }
+}
+
+fn emit_label(emit: Emit) -> String {
+    match emit {
+        Emit::Nothing => String::new(),
+        Emit::Number => "Number".to_string(),
+        Emit::Operator => "Operator".to_string(),
+        Emit::NumberAndOperator => "Number, Operator".to_string(),
+        Emit::Error(TransitionError::IncorrectNumber(error)) => format!("{:?}", error),
+        Emit::Error(error) => format!("{:?}", error),
+    }
+}
+
+// The transitions worth drawing, with the inputs of parallel edges merged into one label.
+// The end and error states ignore all input, so their loops are left out.
+fn edges() -> Vec<(State, State, String)> {
+    let mut edges: Vec<(State, State, Emit, Vec<&str>)> = vec![];
+
+    for t in transition_table() {
+        if t.from == State::End || t.from == State::Error {
+            continue;
+        }
+
+        let existing = edges
+            .iter_mut()
+            .find(|(from, to, emit, _)| *from == t.from && *to == t.to && *emit == t.emit);
+        match existing {
+            Some((_, _, _, inputs)) => inputs.push(input_label(t.input)),
+            None => edges.push((t.from, t.to, t.emit, vec![input_label(t.input)])),
+        }
+    }
+
+    edges
+        .into_iter()
+        .map(|(from, to, emit, inputs)| {
+            let mut label = inputs.join(", ");
+            let emit = emit_label(emit);
+            if !emit.is_empty() {
+                label = format!("{} / {}", label, emit);
+            }
+            (from, to, label)
+        })
+        .collect()
+}
+
+// Render the state machine as a Graphviz DOT digraph
+pub fn to_dot() -> String {
+    let mut dot = String::from("digraph lexer {\n    rankdir=LR;\n");
+
+    for &state in State::ALL.iter() {
+        let shape = match state {
+            State::End => "doublecircle",
+            State::Error => "octagon",
+            _ => "circle",
+        };
+        dot.push_str(&format!("    {:?} [shape={}];\n", state, shape));
+    }
+    for (from, to, label) in edges() {
+        dot.push_str(&format!(
+            "    {:?} -> {:?} [label=\"{}\"];\n",
+            from, to, label
+        ));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+// Render the state machine as a Mermaid state diagram
+pub fn to_mermaid() -> String {
+    let mut mermaid = String::from("stateDiagram-v2\n    [*] --> Initial\n");
+
+    for (from, to, label) in edges() {
+        mermaid.push_str(&format!("    {:?} --> {:?}: {}\n", from, to, label));
+    }
+    mermaid.push_str("    End --> [*]\n");
+
+    mermaid
+}
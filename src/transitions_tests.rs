@@ -0,0 +1,109 @@
+use crate::input_class::InputClass;
+use crate::test_helpers::{lexer_in_state, representative};
+use crate::tokenize::{OperatorKind, State, Token};
+use crate::transitions::{to_dot, to_mermaid, transition, transition_table, Emit};
+
+#[test]
+fn test_table_agrees_with_feed() {
+    for &state in State::ALL.iter() {
+        for &class in InputClass::ALL.iter() {
+            let t = transition(state, class);
+            let c = representative(class);
+
+            let mut lexer = lexer_in_state(state);
+            let buffer = lexer.buffer.clone();
+            let number = || Token::Number(buffer.iter().collect::<String>().parse().unwrap());
+
+            let result = lexer.feed(c);
+
+            let expected = match t.emit {
+                Emit::Nothing => Ok(vec![]),
+                Emit::Number => Ok(vec![number()]),
+                Emit::Operator => Ok(vec![Token::Operator(OperatorKind::Multiply)]),
+                Emit::NumberAndOperator => {
+                    Ok(vec![number(), Token::Operator(OperatorKind::Multiply)])
+                }
+                Emit::Error(error) => Err(error.to_lexing_error(c)),
+            };
+
+            let message = format!("\n state: {:?}, class: {:?}\n\n", state, class);
+            assert_eq!(result, expected, "{}", message);
+            assert_eq!(lexer.state(), t.to, "{}", message);
+
+            if t.push {
+                let mut pushed = buffer.clone();
+                pushed.push(c.unwrap());
+                assert_eq!(lexer.buffer, pushed, "{}", message);
+            } else if let Emit::Number | Emit::NumberAndOperator = t.emit {
+                assert!(lexer.buffer.is_empty(), "{}", message);
+            } else if t.to != State::Error {
+                assert_eq!(lexer.buffer, buffer, "{}", message);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_table_covers_every_state_and_class() {
+    let table = transition_table();
+
+    assert_eq!(table.len(), State::ALL.len() * InputClass::ALL.len());
+    for &state in State::ALL.iter() {
+        for &class in InputClass::ALL.iter() {
+            assert!(table.iter().any(|t| t.from == state && t.input == class));
+        }
+    }
+}
+
+#[test]
+fn test_dot() {
+    let dot = to_dot();
+
+    assert!(dot.starts_with("digraph lexer {\n"));
+    assert!(dot.ends_with("}\n"));
+
+    let expected_lines = vec![
+        "    End [shape=doublecircle];",
+        "    Initial -> NumberZeroInteger [label=\"0\"];",
+        "    Initial -> Initial [label=\"space\"];",
+        "    Number -> Number [label=\"0, 1-9\"];",
+        "    Number -> Initial [label=\"operator / Number, Operator\"];",
+        "    NumberPoint -> Error [label=\"., space, operator, other, newline, EOI / ExpectedDigitAfterPoint\"];",
+        "    WhitespaceBeforeOperator -> End [label=\"EOI\"];",
+    ];
+    for line in expected_lines.into_iter() {
+        assert!(
+            dot.lines().any(|l| l == line),
+            "\n missing: {}\n\n{}",
+            line,
+            dot
+        );
+    }
+
+    // The end and error states ignore their input
+    assert!(!dot.contains("End -> "));
+    assert!(!dot.contains("Error -> "));
+}
+
+#[test]
+fn test_mermaid() {
+    let mermaid = to_mermaid();
+
+    assert!(mermaid.starts_with("stateDiagram-v2\n    [*] --> Initial\n"));
+    assert!(mermaid.ends_with("    End --> [*]\n"));
+
+    let expected_lines = vec![
+        "    Initial --> Number: 1-9",
+        "    NumberZeroInteger --> NumberPoint: .",
+        "    Number --> WhitespaceBeforeOperator: space / Number",
+        "    WhitespaceBeforeOperator --> Error: 0, 1-9 / ExpectedOperator",
+    ];
+    for line in expected_lines.into_iter() {
+        assert!(
+            mermaid.lines().any(|l| l == line),
+            "\n missing: {}\n\n{}",
+            line,
+            mermaid
+        );
+    }
+}